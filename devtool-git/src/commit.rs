@@ -0,0 +1,107 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+const FIELD_SEPARATOR: char = '\x1f';
+const RECORD_SEPARATOR: char = '\x1e';
+
+// Fields are separated by the ASCII unit separator and each commit is
+// terminated by the record separator so that bodies may span lines
+pub const LOG_FORMAT: &str = "%H%x1f%h%x1f%aN%x1f%s%x1f%b%x1e";
+
+#[derive(Debug, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub short_hash: String,
+    pub subject: String,
+    pub body: String,
+    pub author: String,
+}
+
+impl Commit {
+    // Output of "git log" using LOG_FORMAT
+    pub fn parse_log<S>(s: S) -> Vec<Self>
+    where
+        S: AsRef<str>,
+    {
+        s.as_ref()
+            .split(RECORD_SEPARATOR)
+            .filter_map(Self::parse)
+            .collect()
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim_start_matches('\n').splitn(5, FIELD_SEPARATOR);
+        let hash = parts.next().filter(|s| !s.is_empty())?;
+        let short_hash = parts.next()?;
+        let author = parts.next()?;
+        let subject = parts.next()?;
+        let body = parts.next()?;
+        Some(Self {
+            hash: String::from(hash),
+            short_hash: String::from(short_hash),
+            subject: String::from(subject),
+            body: String::from(body.trim_end()),
+            author: String::from(author),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Commit;
+    use rstest::rstest;
+
+    fn commit(hash: &str, subject: &str, body: &str) -> Commit {
+        Commit {
+            hash: String::from(hash),
+            short_hash: String::from(&hash[..7]),
+            subject: String::from(subject),
+            body: String::from(body),
+            author: String::from("Alice Smith"),
+        }
+    }
+
+    #[test]
+    fn parse_log() {
+        assert_eq!(
+            vec![
+                commit(
+                    "0123456789abcdef0123456789abcdef01234567",
+                    "feat: add thing",
+                    "First paragraph\n\nSecond paragraph"
+                ),
+                commit("89abcdef0123456789abcdef0123456789abcdef", "Initial commit", ""),
+            ],
+            Commit::parse_log(
+                "0123456789abcdef0123456789abcdef01234567\x1f0123456\x1fAlice Smith\x1ffeat: add thing\x1fFirst paragraph\n\nSecond paragraph\n\x1e\n89abcdef0123456789abcdef0123456789abcdef\x1f89abcde\x1fAlice Smith\x1fInitial commit\x1f\x1e\n"
+            )
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("\n")]
+    #[case("0123456\x1f0123456\x1fAlice\x1fsubject")]
+    #[case("\x1f0123456\x1fAlice\x1fsubject\x1fbody")]
+    fn parse_log_invalid(#[case] input: &str) {
+        assert!(Commit::parse_log(input).is_empty());
+    }
+}
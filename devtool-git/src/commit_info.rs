@@ -0,0 +1,81 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, PartialEq)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author: String,
+    pub date: DateTime<Utc>,
+}
+
+impl CommitInfo {
+    // Output of "git log" using format "%H%x00%aN%x00%aI"
+    pub fn parse<S>(s: S) -> Option<Self>
+    where
+        S: AsRef<str>,
+    {
+        let mut parts = s.as_ref().splitn(3, '\0');
+        let sha = parts.next().filter(|s| !s.is_empty())?;
+        let author = parts.next()?;
+        let date = DateTime::parse_from_rfc3339(parts.next()?.trim()).ok()?;
+        Some(Self {
+            sha: String::from(sha),
+            author: String::from(author),
+            date: date.with_timezone(&Utc),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommitInfo;
+    use chrono::{TimeZone, Utc};
+    use rstest::rstest;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Some(CommitInfo {
+                sha: String::from("0123456789abcdef0123456789abcdef01234567"),
+                author: String::from("Alice Smith"),
+                date: Utc
+                    .with_ymd_and_hms(2024, 12, 3, 9, 15, 30)
+                    .single()
+                    .expect("must be valid")
+            }),
+            CommitInfo::parse(
+                "0123456789abcdef0123456789abcdef01234567\0Alice Smith\x002024-12-03T10:15:30+01:00"
+            )
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("0123456")]
+    #[case("0123456\0Alice")]
+    #[case("0123456\0Alice\0yesterday")]
+    #[case("\0Alice\x002024-12-03T10:15:30+01:00")]
+    fn parse_invalid(#[case] input: &str) {
+        assert_eq!(None, CommitInfo::parse(input));
+    }
+}
@@ -30,6 +30,7 @@ pub struct GitDescription {
     pub description: String,
     pub tag: String,
     pub offset: Option<Offset>,
+    pub dirty: bool,
 }
 
 impl GitDescription {
@@ -38,26 +39,60 @@ impl GitDescription {
         S: AsRef<str>,
     {
         let s = s.as_ref();
-        if s.is_empty() {
+        let (stripped, dirty) = match s.strip_suffix("-dirty") {
+            Some(stripped) => (stripped, true),
+            None => (s, false),
+        };
+        if stripped.is_empty() {
             return None;
         }
 
-        let parts = s.split('-').collect::<Vec<_>>();
-        match parts.len() {
-            1 => Some(Self {
-                description: String::from(s),
-                tag: String::from(parts[0]),
-                offset: None,
-            }),
-            3 => Some(Self {
-                description: String::from(s),
-                tag: String::from(parts[0]),
-                offset: Some(Offset {
-                    commit: String::from(parts[2]),
-                    count: parts[1].parse::<i32>().ok()?,
-                }),
-            }),
-            _ => None,
+        let (tag, offset) = match Self::split_offset(stripped) {
+            Some((tag, offset)) => (tag, Some(offset)),
+            None => (stripped, None),
+        };
+        if tag.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            description: String::from(s),
+            tag: String::from(tag),
+            offset,
+            dirty,
+        })
+    }
+
+    // Splits off a trailing "-<count>-g<hash>" from the right so that tags
+    // containing hyphens such as "v1.2.3-rc1" are kept intact
+    fn split_offset(s: &str) -> Option<(&str, Offset)> {
+        let (rest, commit) = s.rsplit_once('-')?;
+        let (tag, count) = rest.rsplit_once('-')?;
+        let hash = commit.strip_prefix('g')?;
+        if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        if !count.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        Some((
+            tag,
+            Offset {
+                commit: String::from(commit),
+                count: count.parse::<i32>().ok()?,
+            },
+        ))
+    }
+
+    // True when HEAD is exactly on the tag, whether or not the description
+    // was produced in long form
+    #[must_use]
+    pub const fn is_exact(&self) -> bool {
+        match &self.offset {
+            Some(offset) => offset.count == 0,
+            None => true,
         }
     }
 }
@@ -72,7 +107,8 @@ mod tests {
     #[case(Some(GitDescription {
         description: String::from("v0.0.21"),
         tag: String::from("v0.0.21"),
-        offset: None
+        offset: None,
+        dirty: false
     }), "v0.0.21")]
     #[case(Some(GitDescription {
         description: String::from("v0.0.21-1-gdf3eff3"),
@@ -80,9 +116,89 @@ mod tests {
         offset: Some(Offset {
             commit: String::from("gdf3eff3"),
             count: 1
-        })
+        }),
+        dirty: false
     }), "v0.0.21-1-gdf3eff3")]
+    #[case(None, "-dirty")]
+    #[case(Some(GitDescription {
+        description: String::from("v1.2.3-dirty"),
+        tag: String::from("v1.2.3"),
+        offset: None,
+        dirty: true
+    }), "v1.2.3-dirty")]
+    #[case(Some(GitDescription {
+        description: String::from("v1.2.3-2-gabc-dirty"),
+        tag: String::from("v1.2.3"),
+        offset: Some(Offset {
+            commit: String::from("gabc"),
+            count: 2
+        }),
+        dirty: true
+    }), "v1.2.3-2-gabc-dirty")]
+    #[case(Some(GitDescription {
+        description: String::from("v1.2.3-0-gabcdef0"),
+        tag: String::from("v1.2.3"),
+        offset: Some(Offset {
+            commit: String::from("gabcdef0"),
+            count: 0
+        }),
+        dirty: false
+    }), "v1.2.3-0-gabcdef0")]
+    #[case(Some(GitDescription {
+        description: String::from("v1.2.3-0-gabcdef0-dirty"),
+        tag: String::from("v1.2.3"),
+        offset: Some(Offset {
+            commit: String::from("gabcdef0"),
+            count: 0
+        }),
+        dirty: true
+    }), "v1.2.3-0-gabcdef0-dirty")]
+    #[case(Some(GitDescription {
+        description: String::from("v1.2.3-rc1"),
+        tag: String::from("v1.2.3-rc1"),
+        offset: None,
+        dirty: false
+    }), "v1.2.3-rc1")]
+    #[case(Some(GitDescription {
+        description: String::from("v1.2.3-rc1-4-gabcdef"),
+        tag: String::from("v1.2.3-rc1"),
+        offset: Some(Offset {
+            commit: String::from("gabcdef"),
+            count: 4
+        }),
+        dirty: false
+    }), "v1.2.3-rc1-4-gabcdef")]
+    #[case(Some(GitDescription {
+        description: String::from("release-2024-01-4-gabcdef-dirty"),
+        tag: String::from("release-2024-01"),
+        offset: Some(Offset {
+            commit: String::from("gabcdef"),
+            count: 4
+        }),
+        dirty: true
+    }), "release-2024-01-4-gabcdef-dirty")]
+    #[case(Some(GitDescription {
+        description: String::from("deploy-2-green"),
+        tag: String::from("deploy-2-green"),
+        offset: None,
+        dirty: false
+    }), "deploy-2-green")]
+    #[case(None, "-4-gabcdef")]
     fn test_basics(#[case] expected_result: Option<GitDescription>, #[case] input: &str) {
         assert_eq!(expected_result, GitDescription::parse(input));
     }
+
+    #[rstest]
+    #[case(true, "v1.2.3")]
+    #[case(true, "v1.2.3-dirty")]
+    #[case(true, "v1.2.3-0-gabcdef0")]
+    #[case(true, "v1.2.3-0-gabcdef0-dirty")]
+    #[case(false, "v1.2.3-1-gabcdef0")]
+    #[case(false, "v1.2.3-12-gabcdef0-dirty")]
+    fn is_exact(#[case] expected_result: bool, #[case] input: &str) {
+        assert_eq!(
+            Some(expected_result),
+            GitDescription::parse(input).map(|d| d.is_exact())
+        );
+    }
 }
@@ -32,8 +32,18 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::multiple_crate_versions)]
 #![allow(clippy::option_if_let_else)]
+mod commit;
+mod commit_info;
+mod commit_options;
+mod describe_options;
 mod description;
+mod status_entry;
 mod wrapper;
 
+pub use self::commit::Commit;
+pub use self::commit_info::CommitInfo;
+pub use self::commit_options::CommitOptions;
+pub use self::describe_options::DescribeOptions;
 pub use self::description::GitDescription;
-pub use self::wrapper::{Git, GitError, GitResult};
+pub use self::status_entry::StatusEntry;
+pub use self::wrapper::{Git, GitError, GitResult, DEFAULT_PUSH_RETRIES, DEFAULT_TIMEOUT_SECS};
@@ -0,0 +1,217 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+#[derive(Debug, PartialEq)]
+pub struct StatusEntry {
+    pub index: char,
+    pub worktree: char,
+    pub path: String,
+    pub original_path: Option<String>,
+}
+
+impl StatusEntry {
+    // Parses one line of "git status --porcelain" output such as " M src/main.rs"
+    // or "R  old.rs -> new.rs"
+    pub fn parse<S>(s: S) -> Option<Self>
+    where
+        S: AsRef<str>,
+    {
+        let s = s.as_ref();
+        let mut chars = s.chars();
+        let index = chars.next()?;
+        let worktree = chars.next()?;
+        let path = s.get(2..)?.strip_prefix(' ')?;
+        if path.is_empty() {
+            return None;
+        }
+
+        let (original_path, path) = match path.split_once(" -> ") {
+            Some((original_path, path)) => (Some(String::from(original_path)), path),
+            None => (None, path),
+        };
+
+        Some(Self {
+            index,
+            worktree,
+            path: String::from(path),
+            original_path,
+        })
+    }
+
+    // Parses NUL-delimited "git status --porcelain=v2 -z" output in which
+    // paths are never quoted and the original path of a rename or copy is
+    // the record following the entry
+    #[must_use]
+    pub fn parse_porcelain_v2(s: &str) -> Vec<Self> {
+        let mut entries = Vec::new();
+        let mut records = s.split('\0');
+        while let Some(record) = records.next() {
+            let entry = match record.chars().next() {
+                Some('1') => Self::from_v2_fields(record, 9, None),
+                Some('2') => Self::from_v2_fields(record, 10, records.next()),
+                Some('u') => Self::from_v2_fields(record, 11, None),
+                Some(c @ ('?' | '!')) => {
+                    record.get(2..).filter(|p| !p.is_empty()).map(|path| Self {
+                        index: c,
+                        worktree: c,
+                        path: String::from(path),
+                        original_path: None,
+                    })
+                }
+                _ => None,
+            };
+            entries.extend(entry);
+        }
+        entries
+    }
+
+    fn from_v2_fields(
+        record: &str,
+        field_count: usize,
+        original_path: Option<&str>,
+    ) -> Option<Self> {
+        let fields = record.splitn(field_count, ' ').collect::<Vec<_>>();
+        if fields.len() != field_count {
+            return None;
+        }
+
+        // Porcelain v2 uses "." instead of " " for an unmodified side
+        let mut xy = fields[1].chars().map(|c| if c == '.' { ' ' } else { c });
+        Some(Self {
+            index: xy.next()?,
+            worktree: xy.next()?,
+            path: String::from(fields[field_count - 1]),
+            original_path: original_path.map(String::from),
+        })
+    }
+
+    #[must_use]
+    pub const fn is_untracked(&self) -> bool {
+        self.index == '?' && self.worktree == '?'
+    }
+
+    #[must_use]
+    pub const fn is_ignored(&self) -> bool {
+        self.index == '!' && self.worktree == '!'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatusEntry;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(None, "")]
+    #[case(None, " M")]
+    #[case(None, " M ")]
+    #[case(Some(StatusEntry {
+        index: ' ',
+        worktree: 'M',
+        path: String::from("devtool.yaml"),
+        original_path: None
+    }), " M devtool.yaml")]
+    #[case(Some(StatusEntry {
+        index: 'A',
+        worktree: ' ',
+        path: String::from("src/new.rs"),
+        original_path: None
+    }), "A  src/new.rs")]
+    #[case(Some(StatusEntry {
+        index: '?',
+        worktree: '?',
+        path: String::from("target/"),
+        original_path: None
+    }), "?? target/")]
+    #[case(Some(StatusEntry {
+        index: 'R',
+        worktree: ' ',
+        path: String::from("new.rs"),
+        original_path: Some(String::from("old.rs"))
+    }), "R  old.rs -> new.rs")]
+    fn parse(#[case] expected_result: Option<StatusEntry>, #[case] input: &str) {
+        assert_eq!(expected_result, StatusEntry::parse(input));
+    }
+
+    #[test]
+    fn parse_porcelain_v2() {
+        assert_eq!(
+            vec![
+                StatusEntry {
+                    index: ' ',
+                    worktree: 'M',
+                    path: String::from("src/main.rs"),
+                    original_path: None
+                },
+                StatusEntry {
+                    index: 'R',
+                    worktree: ' ',
+                    path: String::from("new name.rs"),
+                    original_path: Some(String::from("old name.rs"))
+                },
+                StatusEntry {
+                    index: 'U',
+                    worktree: 'U',
+                    path: String::from("conflict.txt"),
+                    original_path: None
+                },
+                StatusEntry {
+                    index: '?',
+                    worktree: '?',
+                    path: String::from("my file.txt"),
+                    original_path: None
+                },
+                StatusEntry {
+                    index: '!',
+                    worktree: '!',
+                    path: String::from("target/"),
+                    original_path: None
+                },
+                StatusEntry {
+                    index: '?',
+                    worktree: '?',
+                    path: String::from("caf\u{e9}.txt"),
+                    original_path: None
+                },
+            ],
+            StatusEntry::parse_porcelain_v2(concat!(
+                "# branch.oid 0123456789abcdef0123456789abcdef01234567\0",
+                "1 .M N... 100644 100644 100644 0123456 0123456 src/main.rs\0",
+                "2 R. N... 100644 100644 100644 0123456 0123456 R100 new name.rs\0old name.rs\0",
+                "u UU N... 100644 100644 100644 100644 0123456 0123456 0123456 conflict.txt\0",
+                "? my file.txt\0",
+                "! target/\0",
+                "? caf\u{e9}.txt\0"
+            ))
+        );
+    }
+
+    #[rstest]
+    #[case(true, "?? foo.txt")]
+    #[case(false, " M foo.txt")]
+    #[case(false, "A  foo.txt")]
+    fn is_untracked(#[case] expected_result: bool, #[case] input: &str) {
+        assert_eq!(
+            Some(expected_result),
+            StatusEntry::parse(input).map(|e| e.is_untracked())
+        );
+    }
+}
@@ -19,35 +19,75 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
-use super::GitDescription;
+use super::commit::LOG_FORMAT;
+use super::{Commit, CommitInfo, CommitOptions, DescribeOptions, GitDescription, StatusEntry};
 use anyhow::anyhow;
-use log::trace;
+use chrono::{DateTime, Utc};
+use log::{trace, warn};
+use std::ffi::OsString;
+use std::io::{stderr, Error as IOError, Read, Result as IOResult, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
+use std::process::{Command, Output, Stdio};
 use std::result::Result as StdResult;
 use std::str::from_utf8;
+use std::thread::{sleep, spawn, JoinHandle};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+pub const DEFAULT_PUSH_RETRIES: u32 = 3;
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Failures that mean the remote could not be reached rather than that it refused the push
+const TRANSIENT_PUSH_ERRORS: [&str; 9] = [
+    "Could not resolve host",
+    "Failed to connect",
+    "Connection refused",
+    "Connection reset",
+    "Connection timed out",
+    "Operation timed out",
+    "RPC failed",
+    "early EOF",
+    "remote end hung up unexpectedly",
+];
+
 #[derive(Debug, Error)]
 pub enum GitError {
-    #[error("command {0} failed with exit code {1}")]
-    CommandFailedWithCode(String, i32),
+    #[error("command {0} failed with exit code {1}{stderr}", stderr = stderr_suffix(.2))]
+    CommandFailedWithCode(String, i32, String),
 
-    #[error("command {0} failed")]
-    CommandFailed(String),
+    #[error("command {0} failed{stderr}", stderr = stderr_suffix(.1))]
+    CommandFailed(String, String),
 
     #[error("e-mail or name is not configured in Git")]
     EmailOrNameNotConfigured,
 
+    #[error("command {0} timed out")]
+    Timeout(String),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
 pub type GitResult<T> = StdResult<T, GitError>;
 
+// Git explains most failures, such as a missing signing key, on standard error
+fn stderr_suffix(stderr: &str) -> String {
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!(": {stderr}")
+    }
+}
+
 #[derive(Debug)]
 pub struct Git {
     pub dir: PathBuf,
+    pub print_commands: bool,
+    pub push_retries: u32,
+    pub timeout: Duration,
 }
 
 struct CommandResult {
@@ -72,20 +112,26 @@ impl CommandResult {
                     .map_err(|e| GitError::Other(anyhow!(e)))?
                     .trim(),
             ),
+            // Leading whitespace is significant in porcelain status output
             stdout: String::from(
                 from_utf8(output.stdout.as_slice())
                     .map_err(|e| GitError::Other(anyhow!(e)))?
-                    .trim(),
+                    .trim_end(),
             ),
         })
     }
 
-    #[allow(clippy::missing_const_for_fn)]
     fn ok(self) -> GitResult<Self> {
         if !self.succeeded {
             match self.exit_code {
-                Some(code) => return Err(GitError::CommandFailedWithCode(self.command, code)),
-                None => return Err(GitError::CommandFailed(self.command)),
+                Some(code) => {
+                    return Err(GitError::CommandFailedWithCode(
+                        self.command,
+                        code,
+                        self.stderr,
+                    ))
+                }
+                None => return Err(GitError::CommandFailed(self.command, self.stderr)),
             };
         }
         Ok(self)
@@ -97,17 +143,26 @@ impl Git {
     where
         P: Into<PathBuf>,
     {
-        Self { dir: dir.into() }
+        Self {
+            dir: dir.into(),
+            print_commands: false,
+            push_retries: DEFAULT_PUSH_RETRIES,
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        }
     }
 
-    pub fn describe(&self) -> GitResult<Option<GitDescription>> {
-        let result = self.run("describe", |_| {})?;
-
-        if result.exit_code == Some(128) && result.stderr.contains("cannot describe anything") {
-            return Ok(None);
-        }
+    pub fn describe(&self, options: &DescribeOptions) -> GitResult<Option<GitDescription>> {
+        Self::description_from_result(self.run("describe", |c| {
+            c.args(Self::describe_args(options));
+        })?)
+    }
 
-        Ok(GitDescription::parse(result.ok()?.stdout))
+    pub fn has_changes_since(&self, tag: &str) -> GitResult<bool> {
+        Self::has_changes_from_result(self.run("diff", |c| {
+            c.arg("--quiet");
+            c.arg(tag);
+            c.arg("HEAD");
+        })?)
     }
 
     pub fn get_current_branch(&self) -> GitResult<String> {
@@ -119,6 +174,16 @@ impl Git {
         Ok(result.stdout)
     }
 
+    pub fn current_commit_hash(&self, short: bool) -> GitResult<Option<String>> {
+        Self::commit_hash_from_result(self.run("rev-parse", |c| {
+            c.arg("--verify");
+            if short {
+                c.arg("--short");
+            }
+            c.arg("HEAD");
+        })?)
+    }
+
     pub fn get_upstream(&self, branch: &str) -> GitResult<Option<String>> {
         let result = self.run("rev-parse", |c| {
             c.arg("--abbrev-ref");
@@ -132,35 +197,239 @@ impl Git {
         Ok(Some(result.ok()?.stdout))
     }
 
-    pub fn create_annotated_tag(&self, tag: &str) -> GitResult<()> {
+    pub fn get_upstream_remote(&self, branch: &str) -> GitResult<Option<String>> {
+        // No branch is checked out when HEAD is detached
+        if branch.is_empty() {
+            return Ok(None);
+        }
+        self.read_config(format!("branch.{branch}.remote"))
+    }
+
+    pub fn default_branch(&self, remote: &str) -> GitResult<Option<String>> {
+        Self::default_branch_from_result(
+            self.run("symbolic-ref", |c| {
+                c.arg("--quiet");
+                c.arg(format!("refs/remotes/{remote}/HEAD"));
+            })?,
+            remote,
+        )
+    }
+
+    pub fn get_remote_url(&self, remote: &str) -> GitResult<Option<String>> {
+        Self::remote_url_from_result(self.run("remote", |c| {
+            c.arg("get-url");
+            c.arg(remote);
+        })?)
+    }
+
+    pub fn create_annotated_tag(&self, tag: &str, message_file: Option<&Path>) -> GitResult<()> {
         self.run("tag", |c| {
-            c.arg("--annotate");
-            c.arg(tag);
-            c.arg("--message");
+            c.args(Self::tag_args(tag, message_file, false));
+        })?
+        .ok()?;
+        Ok(())
+    }
+
+    pub fn create_signed_tag(&self, tag: &str, message_file: Option<&Path>) -> GitResult<()> {
+        self.run("tag", |c| {
+            c.args(Self::tag_args(tag, message_file, true));
+        })?
+        .ok()?;
+        Ok(())
+    }
+
+    pub fn delete_tag(&self, tag: &str) -> GitResult<()> {
+        self.run("tag", |c| {
+            c.arg("--delete");
             c.arg(tag);
         })?
         .ok()?;
         Ok(())
     }
 
+    pub fn list_tags(&self) -> GitResult<Vec<String>> {
+        let result = self
+            .run("tag", |c| {
+                c.arg("--list");
+            })?
+            .ok()?;
+        Ok(result.stdout.lines().map(String::from).collect())
+    }
+
+    pub fn merged_tags(&self) -> GitResult<Vec<String>> {
+        let result = self
+            .run("tag", |c| {
+                c.arg("--merged");
+                c.arg("HEAD");
+            })?
+            .ok()?;
+        Ok(result.stdout.lines().map(String::from).collect())
+    }
+
+    pub fn log_since(&self, tag: Option<&str>) -> GitResult<Vec<(String, String)>> {
+        let result = self
+            .run("log", |c| {
+                c.arg("--format=%aN%x00%s");
+                if let Some(tag) = tag {
+                    c.arg(format!("{tag}..HEAD"));
+                }
+            })?
+            .ok()?;
+        Ok(Self::parse_log_since(&result.stdout))
+    }
+
+    // Non-merge commits reachable from "to" but not from "from", newest first
+    pub fn log_range(&self, from: Option<&str>, to: &str) -> GitResult<Vec<Commit>> {
+        let result = self
+            .run("log", |c| {
+                c.arg("--no-merges");
+                c.arg(format!("--format={LOG_FORMAT}"));
+                c.arg(from.map_or_else(|| String::from(to), |f| format!("{f}..{to}")));
+                c.arg("--");
+            })?
+            .ok()?;
+        Ok(Commit::parse_log(&result.stdout))
+    }
+
+    // All commits reachable from "to" but not from "from", including merges
+    pub fn count_commits(&self, from: &str, to: &str) -> GitResult<usize> {
+        Self::commit_count_from_result(self.run("rev-list", |c| {
+            c.arg("--count");
+            c.arg(format!("{from}..{to}"));
+        })?)
+    }
+
+    pub fn head_subject(&self) -> GitResult<String> {
+        let result = self
+            .run("log", |c| {
+                c.arg("-1");
+                c.arg("--format=%s");
+            })?
+            .ok()?;
+        Ok(result.stdout)
+    }
+
+    // Date of the commit the tag points to, so lightweight and annotated tags
+    // are treated the same way
+    pub fn tag_date(&self, tag: &str) -> GitResult<DateTime<Utc>> {
+        let result = self
+            .run("log", |c| {
+                c.arg("-1");
+                c.arg("--format=%aI");
+                c.arg(tag);
+                c.arg("--");
+            })?
+            .ok()?;
+        Self::parse_date(&result.stdout)
+    }
+
+    // Most recent commit touching the first line starting with "version" in
+    // the manifest
+    pub fn last_version_change<P>(&self, manifest_path: P) -> GitResult<Option<CommitInfo>>
+    where
+        P: AsRef<Path>,
+    {
+        let manifest_path = manifest_path.as_ref();
+        let manifest_path = manifest_path
+            .strip_prefix(&self.dir)
+            .unwrap_or(manifest_path);
+        Self::commit_info_from_result(self.run("log", |c| {
+            c.arg("-1");
+            c.arg("--format=%H%x00%aN%x00%aI");
+            c.arg(format!("-L/^version/,+1:{}", manifest_path.display()));
+        })?)
+    }
+
+    pub fn ls_remote_tags(&self, remote: &str) -> GitResult<Vec<String>> {
+        let result = self
+            .run("ls-remote", |c| {
+                c.arg("--tags");
+                c.arg(remote);
+            })?
+            .ok()?;
+        Ok(Self::parse_ls_remote_tags(&result.stdout))
+    }
+
+    pub fn remote_tag_exists(&self, remote: &str, tag: &str) -> GitResult<bool> {
+        let result = self
+            .run("ls-remote", |c| {
+                c.arg("--tags");
+                c.arg(remote);
+                c.arg(format!("refs/tags/{tag}"));
+            })?
+            .ok()?;
+        Ok(Self::parse_ls_remote_tags(&result.stdout)
+            .iter()
+            .any(|t| t == tag))
+    }
+
+    pub fn fetch(&self, prune_tags: bool) -> GitResult<()> {
+        self.run("fetch", |c| {
+            c.args(Self::fetch_args(prune_tags));
+        })?
+        .ok()?;
+        Ok(())
+    }
+
     pub fn push_all(&self) -> GitResult<()> {
-        self.run("push", |c| {
+        self.run_push(|c| {
             c.arg("--follow-tags");
         })?
         .ok()?;
         Ok(())
     }
 
-    pub fn status(&self, ignored: bool) -> GitResult<String> {
+    pub fn push_tags(&self) -> GitResult<()> {
+        self.run_push(|c| {
+            c.arg("--tags");
+        })?
+        .ok()?;
+        Ok(())
+    }
+
+    pub fn push_dry_run(&self) -> GitResult<String> {
+        let result = self.run_push(Self::push_dry_run_args)?.ok()?;
+        Ok(if result.stderr.is_empty() {
+            result.stdout
+        } else {
+            result.stderr
+        })
+    }
+
+    // Untracked files only make the working directory dirty if include_untracked is set
+    pub fn is_clean(&self, include_untracked: bool) -> GitResult<bool> {
         let result = self
             .run("status", |c| {
                 c.arg("--porcelain");
-                if ignored {
-                    c.arg("--ignored");
+                if !include_untracked {
+                    c.arg("--untracked-files=no");
                 }
             })?
             .ok()?;
-        Ok(result.stdout)
+        Ok(result.stdout.is_empty())
+    }
+
+    pub fn status_entries(&self) -> GitResult<Vec<StatusEntry>> {
+        let result = self
+            .run("status", |c| {
+                c.args(Self::status_args(false));
+            })?
+            .ok()?;
+        Ok(StatusEntry::parse_porcelain_v2(&result.stdout))
+    }
+
+    // Untracked and ignored entries only: prefer this to filtering the
+    // output of status_entries, which does not report ignored paths
+    pub fn status_ignored(&self) -> GitResult<Vec<StatusEntry>> {
+        let result = self
+            .run("status", |c| {
+                c.args(Self::status_args(true));
+            })?
+            .ok()?;
+        Ok(StatusEntry::parse_porcelain_v2(&result.stdout)
+            .into_iter()
+            .filter(|e| e.is_untracked() || e.is_ignored())
+            .collect())
     }
 
     pub fn add<P>(&self, path: P) -> GitResult<()>
@@ -174,13 +443,41 @@ impl Git {
         Ok(())
     }
 
+    pub fn restore(&self, paths: &[PathBuf]) -> GitResult<()> {
+        self.run("restore", |c| {
+            c.arg("--source=HEAD");
+            c.arg("--staged");
+            c.arg("--worktree");
+            c.arg("--");
+            c.args(paths);
+        })?
+        .ok()?;
+        Ok(())
+    }
+
+    pub fn reset_keep(&self, rev: &str) -> GitResult<()> {
+        self.run("reset", |c| {
+            c.arg("--keep");
+            c.arg(rev);
+        })?
+        .ok()?;
+        Ok(())
+    }
+
     pub fn commit<S>(&self, message: S) -> GitResult<()>
+    where
+        S: AsRef<str>,
+    {
+        self.commit_with_options(message, &CommitOptions::default())
+    }
+
+    pub fn commit_with_options<S>(&self, message: S, options: &CommitOptions) -> GitResult<()>
     where
         S: AsRef<str>,
     {
         let result = self.run("commit", |c| {
-            c.arg("--message");
-            c.arg(message.as_ref());
+            c.args(Self::commit_args(message.as_ref(), options));
+            c.envs(Self::commit_envs(options));
         })?;
 
         if result.exit_code == Some(128) && result.stderr.contains("tell me who you are") {
@@ -218,7 +515,203 @@ impl Git {
         Ok(!result.stdout.is_empty())
     }
 
-    fn run<F>(&self, command: &str, build: F) -> GitResult<CommandResult>
+    fn description_from_result(result: CommandResult) -> GitResult<Option<GitDescription>> {
+        if result.exit_code == Some(128) && result.stderr.contains("cannot describe anything") {
+            return Ok(None);
+        }
+
+        // Some repositories yield an empty description instead of failing:
+        // treat this the same as having no tags at all
+        let result = result.ok()?;
+        if result.stdout.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(GitDescription::parse(result.stdout))
+    }
+
+    fn describe_args(options: &DescribeOptions) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if options.dirty {
+            args.push(OsString::from("--dirty"));
+        }
+        if options.long {
+            args.push(OsString::from("--long"));
+        }
+        for pattern in &options.match_patterns {
+            args.push(OsString::from("--match"));
+            args.push(OsString::from(pattern));
+        }
+        args
+    }
+
+    fn commit_args(message: &str, options: &CommitOptions) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if options.sign {
+            args.push(OsString::from("--gpg-sign"));
+        }
+        if options.no_verify {
+            args.push(OsString::from("--no-verify"));
+        }
+        if let Some(author) = &options.author {
+            args.push(OsString::from(format!("--author={author}")));
+        }
+        args.push(OsString::from("--message"));
+        args.push(OsString::from(message));
+        args
+    }
+
+    // Pinning both dates makes the commit hash reproducible: Git's internal
+    // "<timestamp> <offset>" format avoids any ambiguity in date parsing
+    fn commit_envs(options: &CommitOptions) -> Vec<(&'static str, String)> {
+        options.date.map_or_else(Vec::new, |date| {
+            let value = format!("{} {}", date.timestamp(), date.format("%z"));
+            vec![
+                ("GIT_AUTHOR_DATE", value.clone()),
+                ("GIT_COMMITTER_DATE", value),
+            ]
+        })
+    }
+
+    fn tag_args(tag: &str, message_file: Option<&Path>, sign: bool) -> Vec<OsString> {
+        let mut args = vec![
+            OsString::from(if sign { "--sign" } else { "--annotate" }),
+            OsString::from(tag),
+        ];
+        if let Some(path) = message_file {
+            args.push(OsString::from("--file"));
+            args.push(path.as_os_str().to_os_string());
+        } else {
+            args.push(OsString::from("--message"));
+            args.push(OsString::from(tag));
+        }
+        args
+    }
+
+    fn default_branch_from_result(
+        result: CommandResult,
+        remote: &str,
+    ) -> GitResult<Option<String>> {
+        if result.exit_code == Some(1) || result.exit_code == Some(128) {
+            return Ok(None);
+        }
+
+        let result = result.ok()?;
+        Ok(result
+            .stdout
+            .strip_prefix(&format!("refs/remotes/{remote}/"))
+            .map(String::from))
+    }
+
+    // Older versions of Git exit with 128 instead of 2 for a missing remote
+    fn remote_url_from_result(result: CommandResult) -> GitResult<Option<String>> {
+        if matches!(result.exit_code, Some(2 | 128)) && result.stderr.contains("No such remote") {
+            return Ok(None);
+        }
+
+        Ok(Some(result.ok()?.stdout))
+    }
+
+    // HEAD does not resolve in a repository without any commits
+    fn commit_hash_from_result(result: CommandResult) -> GitResult<Option<String>> {
+        if result.exit_code == Some(128) && result.stderr.contains("Needed a single revision") {
+            return Ok(None);
+        }
+
+        Ok(Some(result.ok()?.stdout))
+    }
+
+    fn has_changes_from_result(result: CommandResult) -> GitResult<bool> {
+        if result.exit_code == Some(1) {
+            return Ok(true);
+        }
+
+        result.ok()?;
+        Ok(false)
+    }
+
+    fn commit_count_from_result(result: CommandResult) -> GitResult<usize> {
+        let result = result.ok()?;
+        result.stdout.parse::<usize>().map_err(|e| {
+            GitError::Other(anyhow!("invalid commit count \"{}\": {e}", result.stdout))
+        })
+    }
+
+    fn fetch_args(prune_tags: bool) -> Vec<OsString> {
+        let mut args = vec![OsString::from("--tags")];
+        if prune_tags {
+            args.push(OsString::from("--prune"));
+            args.push(OsString::from("--prune-tags"));
+        }
+        args
+    }
+
+    fn status_args(ignored: bool) -> Vec<OsString> {
+        let mut args = vec![OsString::from("--porcelain=v2"), OsString::from("-z")];
+        if ignored {
+            args.push(OsString::from("--ignored"));
+        }
+        args
+    }
+
+    fn is_transient_push_failure(result: &CommandResult) -> bool {
+        !result.succeeded
+            && !result.stderr.contains("[rejected]")
+            && TRANSIENT_PUSH_ERRORS
+                .iter()
+                .any(|e| result.stderr.contains(e))
+    }
+
+    // 1s, 2s, 4s, ... between successive attempts
+    fn push_retry_delay(attempt: u32) -> Duration {
+        Duration::from_secs(1 << attempt.saturating_sub(1).min(6))
+    }
+
+    fn push_dry_run_args(c: &mut Command) {
+        c.arg("--follow-tags");
+        c.arg("--dry-run");
+    }
+
+    fn commit_info_from_result(result: CommandResult) -> GitResult<Option<CommitInfo>> {
+        // No line matching the pattern or file not yet committed
+        if result.exit_code == Some(128)
+            && (result.stderr.contains("no match") || result.stderr.contains("no path"))
+        {
+            return Ok(None);
+        }
+
+        // Log output is followed by the patch for the line range
+        Ok(result
+            .ok()?
+            .stdout
+            .lines()
+            .next()
+            .and_then(CommitInfo::parse))
+    }
+
+    fn parse_date(s: &str) -> GitResult<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(s)
+            .map(|d| d.with_timezone(&Utc))
+            .map_err(|e| GitError::Other(anyhow!("invalid date \"{s}\": {e}")))
+    }
+
+    fn parse_log_since(s: &str) -> Vec<(String, String)> {
+        s.lines()
+            .filter_map(|line| line.split_once('\0'))
+            .map(|(author, subject)| (String::from(author), String::from(subject)))
+            .collect()
+    }
+
+    fn parse_ls_remote_tags(s: &str) -> Vec<String> {
+        s.lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter_map(|(_, r)| r.strip_prefix("refs/tags/"))
+            .filter(|tag| !tag.ends_with("^{}"))
+            .map(String::from)
+            .collect()
+    }
+
+    fn build_command<F>(&self, command: &str, build: F) -> Command
     where
         F: FnOnce(&mut Command),
     {
@@ -227,12 +720,29 @@ impl Git {
         c.arg(&self.dir);
         c.arg(command);
         build(&mut c);
+        c
+    }
 
+    fn print_command<W: Write>(&self, writer: &mut W, command_str: &str) -> IOResult<()> {
+        if self.print_commands {
+            writeln!(writer, "{command_str}")?;
+        }
+        Ok(())
+    }
+
+    fn run<F>(&self, command: &str, build: F) -> GitResult<CommandResult>
+    where
+        F: FnOnce(&mut Command),
+    {
+        let mut c = self.build_command(command, build);
         let command_str = format!("{c:?}");
-        let result = CommandResult::from_output(
-            command,
-            &c.output().map_err(|e| GitError::Other(anyhow!(e)))?,
-        )?;
+        self.print_command(&mut stderr(), &command_str)
+            .map_err(|e| GitError::Other(anyhow!(e)))?;
+
+        let output = output_with_timeout(&mut c, self.timeout)
+            .map_err(|e| GitError::Other(anyhow!(e)))?
+            .ok_or_else(|| GitError::Timeout(String::from(command)))?;
+        let result = CommandResult::from_output(command, &output)?;
         trace!(
             "command={}, exit_code={:?}, stdout=[{}], stderr=[{}]",
             command_str,
@@ -243,4 +753,570 @@ impl Git {
 
         Ok(result)
     }
+
+    fn run_push<F>(&self, build: F) -> GitResult<CommandResult>
+    where
+        F: Fn(&mut Command),
+    {
+        let mut attempt = 0;
+        loop {
+            // A timed-out push is usually waiting on a prompt so it is not retried
+            let result = self.run("push", &build)?;
+            if attempt >= self.push_retries || !Self::is_transient_push_failure(&result) {
+                return Ok(result);
+            }
+
+            attempt += 1;
+            let delay = Self::push_retry_delay(attempt);
+            warn!(
+                "Push failed ({}), retrying in {}s (attempt {attempt} of {})",
+                result.stderr,
+                delay.as_secs(),
+                self.push_retries
+            );
+            sleep(delay);
+        }
+    }
+}
+
+// Git must never wait for interactive input such as a credential prompt, and the
+// pipes are drained on separate threads so that a chatty child cannot block on a full pipe
+fn output_with_timeout(c: &mut Command, timeout: Duration) -> IOResult<Option<Output>> {
+    fn drain<R>(reader: Option<R>) -> JoinHandle<IOResult<Vec<u8>>>
+    where
+        R: Read + Send + 'static,
+    {
+        spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut reader) = reader {
+                reader.read_to_end(&mut buffer)?;
+            }
+            Ok(buffer)
+        })
+    }
+
+    let mut child = c
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .spawn()?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+
+        sleep(POLL_INTERVAL);
+    };
+
+    let join = |handle: JoinHandle<IOResult<Vec<u8>>>| {
+        handle
+            .join()
+            .unwrap_or_else(|_| Err(IOError::other("output reader panicked")))
+    };
+    Ok(Some(Output {
+        status,
+        stdout: join(stdout)?,
+        stderr: join(stderr)?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        output_with_timeout, CommandResult, CommitOptions, DescribeOptions, Git, GitDescription,
+        DEFAULT_TIMEOUT_SECS,
+    };
+    use anyhow::Result;
+    use chrono::{DateTime, TimeZone, Utc};
+    use rstest::rstest;
+    use std::ffi::OsString;
+    use std::path::Path;
+    use std::process::Command;
+    use std::time::Duration;
+
+    fn command_result(exit_code: i32, stdout: &str, stderr: &str) -> CommandResult {
+        CommandResult {
+            command: String::from("describe"),
+            succeeded: exit_code == 0,
+            exit_code: Some(exit_code),
+            stderr: String::from(stderr),
+            stdout: String::from(stdout),
+        }
+    }
+
+    #[test]
+    fn ok_failure_includes_stderr() {
+        let e = command_result(128, "", "error: gpg failed to sign the data")
+            .ok()
+            .err()
+            .expect("must fail");
+        assert!(e.to_string().contains("gpg failed to sign the data"));
+    }
+
+    #[rstest]
+    #[case("", "")]
+    #[case(": fatal: not a git repository", "fatal: not a git repository")]
+    fn stderr_suffix(#[case] expected_result: &str, #[case] stderr: &str) {
+        assert_eq!(expected_result, super::stderr_suffix(stderr));
+    }
+
+    #[rstest]
+    #[case(None, 0, "", "")]
+    #[case(None, 128, "", "fatal: No names found, cannot describe anything.")]
+    #[case(GitDescription::parse("v0.0.21"), 0, "v0.0.21", "")]
+    #[case(
+        GitDescription::parse("v0.0.21-0-gdf3eff3"),
+        0,
+        "v0.0.21-0-gdf3eff3",
+        ""
+    )]
+    fn description_from_result(
+        #[case] expected_result: Option<GitDescription>,
+        #[case] exit_code: i32,
+        #[case] stdout: &str,
+        #[case] stderr: &str,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result,
+            Git::description_from_result(command_result(exit_code, stdout, stderr))?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(&[], false, false, &[])]
+    #[case(&["--dirty"], true, false, &[])]
+    #[case(&["--long"], false, true, &[])]
+    #[case(
+        &["--dirty", "--long", "--match", "v[0-9]*", "--match", "[0-9]*"],
+        true,
+        true,
+        &["v[0-9]*", "[0-9]*"]
+    )]
+    fn describe_args(
+        #[case] expected_result: &[&str],
+        #[case] dirty: bool,
+        #[case] long: bool,
+        #[case] match_patterns: &[&str],
+    ) {
+        let options = DescribeOptions {
+            dirty,
+            long,
+            match_patterns: match_patterns.iter().map(|s| String::from(*s)).collect(),
+        };
+        assert_eq!(
+            expected_result
+                .iter()
+                .map(OsString::from)
+                .collect::<Vec<_>>(),
+            Git::describe_args(&options)
+        );
+    }
+
+    #[rstest]
+    #[case(&["--message", "Bump version"], false, false)]
+    #[case(&["--gpg-sign", "--message", "Bump version"], true, false)]
+    #[case(&["--no-verify", "--message", "Bump version"], false, true)]
+    #[case(
+        &["--gpg-sign", "--no-verify", "--message", "Bump version"],
+        true,
+        true
+    )]
+    fn commit_args(#[case] expected_result: &[&str], #[case] sign: bool, #[case] no_verify: bool) {
+        assert_eq!(
+            expected_result
+                .iter()
+                .map(OsString::from)
+                .collect::<Vec<_>>(),
+            Git::commit_args(
+                "Bump version",
+                &CommitOptions {
+                    sign,
+                    no_verify,
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn commit_args_author() {
+        assert_eq!(
+            vec![
+                OsString::from("--author=Release Bot <bot@example.com>"),
+                OsString::from("--message"),
+                OsString::from("Bump version"),
+            ],
+            Git::commit_args(
+                "Bump version",
+                &CommitOptions {
+                    author: Some(String::from("Release Bot <bot@example.com>")),
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn commit_envs() -> Result<()> {
+        assert!(Git::commit_envs(&CommitOptions::default()).is_empty());
+        assert_eq!(
+            vec![
+                ("GIT_AUTHOR_DATE", String::from("1704164645 +0100")),
+                ("GIT_COMMITTER_DATE", String::from("1704164645 +0100")),
+            ],
+            Git::commit_envs(&CommitOptions {
+                date: Some(DateTime::parse_from_rfc3339("2024-01-02T04:04:05+01:00")?),
+                ..Default::default()
+            })
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        &["--annotate", "v1.2.3", "--message", "v1.2.3"],
+        "v1.2.3",
+        None,
+        false
+    )]
+    #[case(
+        &["--annotate", "v1.2.3", "--file", "/notes/v1.2.3.md"],
+        "v1.2.3",
+        Some("/notes/v1.2.3.md"),
+        false
+    )]
+    #[case(&["--sign", "v1.2.3", "--message", "v1.2.3"], "v1.2.3", None, true)]
+    fn tag_args(
+        #[case] expected_result: &[&str],
+        #[case] tag: &str,
+        #[case] message_file: Option<&str>,
+        #[case] sign: bool,
+    ) {
+        assert_eq!(
+            expected_result
+                .iter()
+                .map(OsString::from)
+                .collect::<Vec<_>>(),
+            Git::tag_args(tag, message_file.map(Path::new), sign)
+        );
+    }
+
+    #[rstest]
+    #[case(Some("main"), 0, "refs/remotes/origin/main", "")]
+    #[case(Some("develop"), 0, "refs/remotes/origin/develop", "")]
+    #[case(None, 0, "refs/remotes/upstream/main", "")]
+    #[case(None, 1, "", "")]
+    #[case(
+        None,
+        128,
+        "",
+        "fatal: ref refs/remotes/origin/HEAD is not a symbolic ref"
+    )]
+    fn default_branch_from_result(
+        #[case] expected_result: Option<&str>,
+        #[case] exit_code: i32,
+        #[case] stdout: &str,
+        #[case] stderr: &str,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result.map(String::from),
+            Git::default_branch_from_result(command_result(exit_code, stdout, stderr), "origin")?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("", false)]
+    #[case("\"git\" \"-C\" \"dir\" \"describe\" \"--dirty\"\n", true)]
+    fn print_command(#[case] expected_output: &str, #[case] print_commands: bool) -> Result<()> {
+        let mut git = Git::new("dir");
+        git.print_commands = print_commands;
+        let c = git.build_command("describe", |c| {
+            c.arg("--dirty");
+        });
+
+        let mut output = Vec::new();
+        git.print_command(&mut output, &format!("{c:?}"))?;
+        assert_eq!(expected_output, String::from_utf8(output)?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        Some("git@github.com:rcook/devtool.git"),
+        0,
+        "git@github.com:rcook/devtool.git",
+        ""
+    )]
+    #[case(None, 2, "", "error: No such remote 'origin'")]
+    #[case(None, 128, "", "fatal: No such remote 'origin'")]
+    fn remote_url_from_result(
+        #[case] expected_result: Option<&str>,
+        #[case] exit_code: i32,
+        #[case] stdout: &str,
+        #[case] stderr: &str,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result.map(String::from),
+            Git::remote_url_from_result(command_result(exit_code, stdout, stderr))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn remote_url_from_result_failure() {
+        assert!(Git::remote_url_from_result(command_result(
+            128,
+            "",
+            "fatal: not a git repository"
+        ))
+        .is_err());
+    }
+
+    #[rstest]
+    #[case(Some(false), 0)]
+    #[case(Some(true), 1)]
+    #[case(None, 128)]
+    fn has_changes_from_result(#[case] expected_result: Option<bool>, #[case] exit_code: i32) {
+        assert_eq!(
+            expected_result,
+            Git::has_changes_from_result(command_result(exit_code, "", "")).ok()
+        );
+    }
+
+    #[rstest]
+    #[case(Some(0), 0, "0")]
+    #[case(Some(12), 0, "12")]
+    #[case(None, 0, "")]
+    #[case(None, 128, "")]
+    fn commit_count_from_result(
+        #[case] expected_result: Option<usize>,
+        #[case] exit_code: i32,
+        #[case] stdout: &str,
+    ) {
+        assert_eq!(
+            expected_result,
+            Git::commit_count_from_result(command_result(exit_code, stdout, "")).ok()
+        );
+    }
+
+    #[rstest]
+    #[case(vec!["--tags"], false)]
+    #[case(vec!["--tags", "--prune", "--prune-tags"], true)]
+    fn fetch_args(#[case] expected_args: Vec<&str>, #[case] prune_tags: bool) {
+        assert_eq!(
+            expected_args
+                .into_iter()
+                .map(OsString::from)
+                .collect::<Vec<_>>(),
+            Git::fetch_args(prune_tags)
+        );
+    }
+
+    #[rstest]
+    #[case(vec!["--porcelain=v2", "-z"], false)]
+    #[case(vec!["--porcelain=v2", "-z", "--ignored"], true)]
+    fn status_args(#[case] expected_args: Vec<&str>, #[case] ignored: bool) {
+        assert_eq!(
+            expected_args
+                .into_iter()
+                .map(OsString::from)
+                .collect::<Vec<_>>(),
+            Git::status_args(ignored)
+        );
+    }
+
+    #[rstest]
+    #[case(
+        true,
+        Some("0123456789abcdef0123456789abcdef01234567"),
+        0,
+        "0123456789abcdef0123456789abcdef01234567",
+        ""
+    )]
+    #[case(true, None, 128, "", "fatal: Needed a single revision")]
+    #[case(false, None, 128, "", "fatal: not a git repository")]
+    fn commit_hash_from_result(
+        #[case] expected_ok: bool,
+        #[case] expected_result: Option<&str>,
+        #[case] exit_code: i32,
+        #[case] stdout: &str,
+        #[case] stderr: &str,
+    ) {
+        let result = Git::commit_hash_from_result(command_result(exit_code, stdout, stderr));
+        assert_eq!(expected_ok, result.is_ok());
+        assert_eq!(expected_result, result.ok().flatten().as_deref());
+    }
+
+    #[rstest]
+    #[case(false, 0, "")]
+    #[case(
+        true,
+        128,
+        "fatal: unable to access 'https://example.com/repo.git/': Could not resolve host: example.com"
+    )]
+    #[case(
+        true,
+        128,
+        "ssh: connect to host example.com port 22: Connection timed out\nfatal: Could not read from remote repository."
+    )]
+    #[case(
+        true,
+        1,
+        "error: RPC failed; curl 56 Recv failure: Connection reset by peer\nfatal: the remote end hung up unexpectedly"
+    )]
+    #[case(
+        false,
+        1,
+        "To example.com:repo.git\n ! [rejected]        main -> main (non-fast-forward)\nerror: failed to push some refs"
+    )]
+    #[case(
+        false,
+        128,
+        "git@example.com: Permission denied (publickey).\nfatal: Could not read from remote repository."
+    )]
+    fn is_transient_push_failure(
+        #[case] expected_result: bool,
+        #[case] exit_code: i32,
+        #[case] stderr: &str,
+    ) {
+        assert_eq!(
+            expected_result,
+            Git::is_transient_push_failure(&command_result(exit_code, "", stderr))
+        );
+    }
+
+    #[rstest]
+    #[case(1, 1)]
+    #[case(2, 2)]
+    #[case(4, 3)]
+    #[case(64, 100)]
+    fn push_retry_delay(#[case] expected_secs: u64, #[case] attempt: u32) {
+        assert_eq!(expected_secs, Git::push_retry_delay(attempt).as_secs());
+    }
+
+    #[test]
+    fn push_dry_run_args() {
+        let c = Git::new("dir").build_command("push", Git::push_dry_run_args);
+        assert_eq!(
+            "\"git\" \"-C\" \"dir\" \"push\" \"--follow-tags\" \"--dry-run\"",
+            format!("{c:?}")
+        );
+    }
+
+    #[rstest]
+    #[case(None, 0, "", "")]
+    #[case(
+        None,
+        128,
+        "",
+        "fatal: -L parameter '^version' starting at line 1: no match"
+    )]
+    #[case(None, 128, "", "fatal: There is no path Cargo.toml in the commit")]
+    #[case(
+        Some("0123456789abcdef0123456789abcdef01234567"),
+        0,
+        "0123456789abcdef0123456789abcdef01234567\0Alice\x002024-12-03T10:15:30Z\n\ndiff --git a/Cargo.toml b/Cargo.toml\n-version = \"0.1.0\"\n+version = \"0.2.0\"",
+        ""
+    )]
+    fn commit_info_from_result(
+        #[case] expected_result: Option<&str>,
+        #[case] exit_code: i32,
+        #[case] stdout: &str,
+        #[case] stderr: &str,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result.map(String::from),
+            Git::commit_info_from_result(command_result(exit_code, stdout, stderr))?.map(|c| c.sha)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn commit_info_from_result_failure() {
+        assert!(Git::commit_info_from_result(command_result(
+            128,
+            "",
+            "fatal: not a git repository"
+        ))
+        .is_err());
+    }
+
+    #[rstest]
+    #[case(Some((2024, 12, 3, 10, 15, 30)), "2024-12-03T10:15:30Z")]
+    #[case(Some((2024, 12, 3, 10, 15, 30)), "2024-12-03T11:15:30+01:00")]
+    #[case(Some((2024, 12, 4, 1, 0, 0)), "2024-12-03T17:00:00-08:00")]
+    #[case(None, "")]
+    #[case(None, "2024-12-03")]
+    fn parse_date(
+        #[case] expected_result: Option<(i32, u32, u32, u32, u32, u32)>,
+        #[case] input: &str,
+    ) {
+        assert_eq!(
+            expected_result.map(|(year, month, day, hour, min, sec)| Utc
+                .with_ymd_and_hms(year, month, day, hour, min, sec)
+                .single()
+                .expect("must be valid")),
+            Git::parse_date(input).ok()
+        );
+    }
+
+    #[rstest]
+    #[case(&[], "")]
+    #[case(&[("Alice", "Fix bug")], "Alice\0Fix bug")]
+    #[case(
+        &[("Alice", "Fix bug"), ("Bob Smith", "Add feature"), ("Alice", "")],
+        "Alice\0Fix bug\nBob Smith\0Add feature\nAlice\0"
+    )]
+    #[case(&[("Alice", "Fix bug")], "malformed\nAlice\0Fix bug")]
+    fn parse_log_since(#[case] expected_result: &[(&str, &str)], #[case] input: &str) {
+        assert_eq!(
+            expected_result
+                .iter()
+                .map(|(a, s)| (String::from(*a), String::from(*s)))
+                .collect::<Vec<_>>(),
+            Git::parse_log_since(input)
+        );
+    }
+
+    #[test]
+    fn parse_ls_remote_tags() {
+        assert_eq!(
+            vec!["v0.0.1", "v0.0.2", "lightweight"],
+            Git::parse_ls_remote_tags(
+                "d3b07384d113edec49eaa6238ad5ff0000000000\trefs/tags/v0.0.1\n\
+                 c157a79031e1c40f85931829bc5fc55200000000\trefs/tags/v0.0.1^{}\n\
+                 0b2d6a2b2a7e0b1a9e8ae63b76a1d6f100000000\trefs/tags/v0.0.2\n\
+                 5d41402abc4b2a76b9719d911017c59200000000\trefs/tags/v0.0.2^{}\n\
+                 7d793037a0760186574b0282f2f435e700000000\trefs/tags/lightweight"
+            )
+        );
+    }
+
+    #[test]
+    fn output_with_timeout_does_not_wait_for_stdin() -> Result<()> {
+        let output = output_with_timeout(
+            Command::new("git").args(["hash-object", "--stdin"]),
+            Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        )?
+        .expect("must not time out");
+        assert!(output.status.success());
+        assert_eq!(
+            "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            String::from_utf8(output.stdout)?.trim()
+        );
+        Ok(())
+    }
 }
@@ -32,6 +32,10 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::multiple_crate_versions)]
 #![allow(clippy::option_if_let_else)]
+mod bump;
+mod change_kind;
 mod version;
 
+pub use self::bump::Bump;
+pub use self::change_kind::ChangeKind;
 pub use self::version::{Version, VersionParseError, VersionParseResult};
@@ -19,8 +19,10 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
+use crate::{Bump, ChangeKind};
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 use std::result::Result as StdResult;
 use std::str::FromStr;
@@ -36,37 +38,210 @@ pub type VersionParseResult<T> = StdResult<T, VersionParseError>;
 
 #[derive(Debug)]
 pub struct Version {
+    epoch: Option<u64>,
     inner: Box<dyn VersionInner>,
+    prerelease: Option<String>,
+    build_metadata: Option<String>,
 }
 
 impl Version {
+    #[must_use]
+    pub const fn epoch(&self) -> Option<u64> {
+        self.epoch
+    }
+
+    #[must_use]
+    pub fn major(&self) -> u64 {
+        self.inner.major()
+    }
+
+    #[must_use]
+    pub fn minor(&self) -> Option<u64> {
+        self.inner.minor()
+    }
+
+    #[must_use]
+    pub fn patch(&self) -> Option<u64> {
+        self.inner.patch()
+    }
+
+    #[must_use]
+    pub fn prerelease(&self) -> Option<&str> {
+        self.prerelease.as_deref()
+    }
+
+    #[must_use]
+    pub fn build_metadata(&self) -> Option<&str> {
+        self.build_metadata.as_deref()
+    }
+
     pub fn set_prefix(&mut self, value: bool) {
         self.inner.set_prefix(value);
     }
 
-    pub fn increment(&mut self) {
-        self.inner.increment();
+    // Prerelease and build metadata only describe the version being replaced
+    pub fn increment(&mut self) -> VersionParseResult<()> {
+        self.inner.increment()?;
+        self.clear_suffixes();
+        Ok(())
+    }
+
+    pub fn increment_major(&mut self) -> VersionParseResult<()> {
+        self.inner.increment_major()?;
+        self.clear_suffixes();
+        Ok(())
+    }
+
+    pub fn increment_minor(&mut self) -> VersionParseResult<()> {
+        self.inner.increment_minor()?;
+        self.clear_suffixes();
+        Ok(())
+    }
+
+    pub fn increment_patch(&mut self) -> VersionParseResult<()> {
+        self.inner.increment_patch()?;
+        self.clear_suffixes();
+        Ok(())
+    }
+
+    pub fn next(&self) -> VersionParseResult<Self> {
+        let mut version = self.dupe();
+        version.increment()?;
+        Ok(version)
+    }
+
+    pub fn next_at(&self, index: usize) -> VersionParseResult<Self> {
+        let mut version = self.dupe();
+        version.inner.increment_at(index)?;
+        version.clear_suffixes();
+        Ok(version)
+    }
+
+    pub fn bump(&self, bump: Bump, zerover: bool) -> VersionParseResult<Self> {
+        self.next_at(bump.index(self.major(), zerover))
+    }
+
+    // Breaks ties between versions of equal precedence on formatting and build
+    // metadata so that sorting is deterministic
+    #[must_use]
+    pub fn cmp_exact(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+            .then_with(|| {
+                self.inner
+                    .components()
+                    .len()
+                    .cmp(&other.inner.components().len())
+            })
+            .then_with(|| self.epoch.is_some().cmp(&other.epoch.is_some()))
+            .then_with(|| self.inner.has_prefix().cmp(&other.inner.has_prefix()))
+            .then_with(|| self.build_metadata.cmp(&other.build_metadata))
+    }
+
+    #[must_use]
+    pub fn change_kind(&self, newer: &Self) -> ChangeKind {
+        match self
+            .epoch
+            .unwrap_or_default()
+            .cmp(&newer.epoch.unwrap_or_default())
+        {
+            Ordering::Less => return ChangeKind::Major,
+            Ordering::Greater => return ChangeKind::Downgrade,
+            Ordering::Equal => {}
+        }
+
+        let lhs = self.inner.components();
+        let rhs = newer.inner.components();
+        let component = |c: &[u64], i: usize| c.get(i).copied().unwrap_or_default();
+        (0..lhs.len().max(rhs.len()))
+            .find_map(|i| match component(&lhs, i).cmp(&component(&rhs, i)) {
+                Ordering::Less => Some(ChangeKind::from_index(i)),
+                Ordering::Greater => Some(ChangeKind::Downgrade),
+                Ordering::Equal => None,
+            })
+            .unwrap_or(ChangeKind::None)
     }
 
     #[must_use]
     pub fn dupe(&self) -> Self {
         Self {
+            epoch: self.epoch,
             inner: self.inner.dupe(),
+            prerelease: self.prerelease.clone(),
+            build_metadata: self.build_metadata.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn truncate_to(&self, components: usize) -> Self {
+        Self {
+            epoch: self.epoch,
+            inner: self.inner.truncate_to(components),
+            prerelease: None,
+            build_metadata: None,
         }
     }
+
+    fn clear_suffixes(&mut self) {
+        self.prerelease = None;
+        self.build_metadata = None;
+    }
 }
 
 impl Clone for Version {
     fn clone(&self) -> Self {
-        Self {
-            inner: self.inner.dupe(),
-        }
+        self.dupe()
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Compares epoch, numeric components and prerelease only: missing components
+// count as zero and prefix and build metadata are ignored
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self.inner.components();
+        let rhs = other.inner.components();
+        let len = lhs.len().max(rhs.len());
+        let padded = |c: &[u64]| {
+            (0..len)
+                .map(|i| c.get(i).copied().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        self.epoch
+            .unwrap_or_default()
+            .cmp(&other.epoch.unwrap_or_default())
+            .then_with(|| padded(&lhs).cmp(&padded(&rhs)))
+            .then_with(|| {
+                compare_prerelease(self.prerelease.as_deref(), other.prerelease.as_deref())
+            })
     }
 }
 
 impl Display for Version {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "{}", self.inner)
+        if let Some(epoch) = self.epoch {
+            write!(f, "{epoch}:")?;
+        }
+        write!(f, "{}", self.inner)?;
+        if let Some(prerelease) = &self.prerelease {
+            write!(f, "-{prerelease}")?;
+        }
+        if let Some(build_metadata) = &self.build_metadata {
+            write!(f, "+{build_metadata}")?;
+        }
+        Ok(())
     }
 }
 
@@ -74,8 +249,27 @@ impl FromStr for Version {
     type Err = VersionParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (epoch, s) = match s.split_once(':') {
+            Some((epoch, rest)) => (
+                Some(
+                    epoch
+                        .parse::<u64>()
+                        .map_err(|e| VersionParseError::Other(anyhow!(e)))?,
+                ),
+                rest,
+            ),
+            None => (None, s),
+        };
+        let (s, build_metadata) = split_suffix(s, '+')?;
+        check_negative_component(s)?;
+        let (s, prerelease) = split_suffix(s, '-')?;
         let inner = parse_version_inner(s)?;
-        Ok(Self { inner })
+        Ok(Self {
+            epoch,
+            inner,
+            prerelease,
+            build_metadata,
+        })
     }
 }
 
@@ -100,9 +294,95 @@ impl Serialize for Version {
 }
 
 pub trait VersionInner: Debug + Display + Send + Sync {
+    fn has_prefix(&self) -> bool;
     fn set_prefix(&mut self, value: bool);
-    fn increment(&mut self);
+    fn components(&self) -> Vec<u64>;
+    fn increment_at(&mut self, index: usize) -> VersionParseResult<()>;
     fn dupe(&self) -> Box<dyn VersionInner>;
+    fn truncate_to(&self, components: usize) -> Box<dyn VersionInner>;
+
+    // Every version has at least a major component
+    fn major(&self) -> u64 {
+        self.components().first().copied().unwrap_or_default()
+    }
+
+    fn minor(&self) -> Option<u64> {
+        self.components().get(1).copied()
+    }
+
+    fn patch(&self) -> Option<u64> {
+        self.components().get(2).copied()
+    }
+
+    // Increments the last component
+    fn increment(&mut self) -> VersionParseResult<()> {
+        self.increment_at(self.components().len() - 1)
+    }
+
+    fn increment_major(&mut self) -> VersionParseResult<()> {
+        self.increment_named(0, "major")
+    }
+
+    fn increment_minor(&mut self) -> VersionParseResult<()> {
+        self.increment_named(1, "minor")
+    }
+
+    fn increment_patch(&mut self) -> VersionParseResult<()> {
+        self.increment_named(2, "patch")
+    }
+
+    fn increment_named(&mut self, index: usize, name: &str) -> VersionParseResult<()> {
+        if index >= self.components().len() {
+            return Err(VersionParseError::Other(anyhow!(
+                "version {self} has no {name} component"
+            )));
+        }
+        self.increment_at(index)
+    }
+}
+
+// Splits off a semver prerelease ("-rc.1") or build metadata ("+abc") suffix
+fn split_suffix(s: &str, separator: char) -> VersionParseResult<(&str, Option<String>)> {
+    let Some((s, suffix)) = s.split_once(separator) else {
+        return Ok((s, None));
+    };
+
+    if suffix.split('.').any(|identifier| {
+        identifier.is_empty()
+            || !identifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    }) {
+        return Err(VersionParseError::Other(anyhow!(
+            "invalid version suffix {separator}{suffix}"
+        )));
+    }
+
+    Ok((s, Some(String::from(suffix))))
+}
+
+// Semver precedence: a version without a prerelease is greater than one with,
+// numeric identifiers compare numerically and are lower than alphanumeric ones
+fn compare_prerelease(lhs: Option<&str>, rhs: Option<&str>) -> Ordering {
+    let (lhs, rhs) = match (lhs, rhs) {
+        (None, None) => return Ordering::Equal,
+        (None, Some(_)) => return Ordering::Greater,
+        (Some(_), None) => return Ordering::Less,
+        (Some(lhs), Some(rhs)) => (lhs, rhs),
+    };
+
+    let lhs = lhs.split('.').collect::<Vec<_>>();
+    let rhs = rhs.split('.').collect::<Vec<_>>();
+    lhs.iter()
+        .zip(&rhs)
+        .map(|(a, b)| match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        })
+        .find(|o| *o != Ordering::Equal)
+        .unwrap_or_else(|| lhs.len().cmp(&rhs.len()))
 }
 
 fn parse_version_inner(s: &str) -> VersionParseResult<Box<dyn VersionInner>> {
@@ -113,18 +393,18 @@ fn parse_version_inner(s: &str) -> VersionParseResult<Box<dyn VersionInner>> {
     match parts.len() {
         1 => Ok(Box::new(VersionSingleton {
             has_prefix,
-            major: parts[0].parse::<i32>().map_err(|e| anyhow!(e))?,
+            major: parse_component(parts[0])?,
         })),
         2 => Ok(Box::new(VersionPair {
             has_prefix,
-            major: parts[0].parse::<i32>().map_err(|e| anyhow!(e))?,
-            minor: parts[1].parse::<i32>().map_err(|e| anyhow!(e))?,
+            major: parse_component(parts[0])?,
+            minor: parse_component(parts[1])?,
         })),
         3 => Ok(Box::new(VersionTriple {
             has_prefix,
-            major: parts[0].parse::<i32>().map_err(|e| anyhow!(e))?,
-            minor: parts[1].parse::<i32>().map_err(|e| anyhow!(e))?,
-            build: parts[2].parse::<i32>().map_err(|e| anyhow!(e))?,
+            major: parse_component(parts[0])?,
+            minor: parse_component(parts[1])?,
+            build: parse_component(parts[2])?,
         })),
         _ => Err(VersionParseError::Other(anyhow!(
             "could not parse {} as version",
@@ -133,19 +413,74 @@ fn parse_version_inner(s: &str) -> VersionParseResult<Box<dyn VersionInner>> {
     }
 }
 
+// A minus sign starting a component would otherwise be taken as the start of
+// the prerelease suffix, so it is rejected before the suffix is split off
+fn check_negative_component(s: &str) -> VersionParseResult<()> {
+    let s = s.strip_prefix('v').unwrap_or(s);
+    let Some(index) = s.find('-') else {
+        return Ok(());
+    };
+    let rest = &s[index + 1..];
+    if (index == 0 || s[..index].ends_with('.')) && rest.starts_with(|c: char| c.is_ascii_digit()) {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let component = &s[index..=index + digits];
+        return Err(VersionParseError::Other(anyhow!(
+            "version component {component} must not be negative"
+        )));
+    }
+    Ok(())
+}
+
+// Components are unsigned so that incrementing can never wrap to a negative
+fn parse_component(s: &str) -> VersionParseResult<u64> {
+    s.parse::<u64>()
+        .map_err(|e| VersionParseError::Other(anyhow!("invalid version component \"{s}\": {e}")))
+}
+
+fn checked_increment(value: u64, version: &dyn VersionInner) -> VersionParseResult<u64> {
+    value.checked_add(1).ok_or_else(|| {
+        VersionParseError::Other(anyhow!(
+            "version {} cannot be incremented without overflow",
+            version
+        ))
+    })
+}
+
+fn invalid_index(version: &dyn VersionInner, index: usize) -> VersionParseError {
+    VersionParseError::Other(anyhow!(
+        "version {} has no component at index {}",
+        version,
+        index
+    ))
+}
+
 #[derive(Debug)]
 struct VersionSingleton {
     has_prefix: bool,
-    major: i32,
+    major: u64,
 }
 
 impl VersionInner for VersionSingleton {
+    fn has_prefix(&self) -> bool {
+        self.has_prefix
+    }
+
     fn set_prefix(&mut self, value: bool) {
         self.has_prefix = value;
     }
 
-    fn increment(&mut self) {
-        self.major += 1;
+    fn components(&self) -> Vec<u64> {
+        vec![self.major]
+    }
+
+    fn increment_at(&mut self, index: usize) -> VersionParseResult<()> {
+        match index {
+            0 => self.major = checked_increment(self.major, self)?,
+            _ => return Err(invalid_index(self, index)),
+        }
+        Ok(())
     }
 
     fn dupe(&self) -> Box<dyn VersionInner> {
@@ -154,6 +489,10 @@ impl VersionInner for VersionSingleton {
             major: self.major,
         })
     }
+
+    fn truncate_to(&self, _components: usize) -> Box<dyn VersionInner> {
+        self.dupe()
+    }
 }
 
 impl Display for VersionSingleton {
@@ -168,17 +507,33 @@ impl Display for VersionSingleton {
 #[derive(Debug)]
 struct VersionPair {
     has_prefix: bool,
-    major: i32,
-    minor: i32,
+    major: u64,
+    minor: u64,
 }
 
 impl VersionInner for VersionPair {
+    fn has_prefix(&self) -> bool {
+        self.has_prefix
+    }
+
     fn set_prefix(&mut self, value: bool) {
         self.has_prefix = value;
     }
 
-    fn increment(&mut self) {
-        self.minor += 1;
+    fn components(&self) -> Vec<u64> {
+        vec![self.major, self.minor]
+    }
+
+    fn increment_at(&mut self, index: usize) -> VersionParseResult<()> {
+        match index {
+            0 => {
+                self.major = checked_increment(self.major, self)?;
+                self.minor = 0;
+            }
+            1 => self.minor = checked_increment(self.minor, self)?,
+            _ => return Err(invalid_index(self, index)),
+        }
+        Ok(())
     }
 
     fn dupe(&self) -> Box<dyn VersionInner> {
@@ -188,6 +543,17 @@ impl VersionInner for VersionPair {
             minor: self.minor,
         })
     }
+
+    fn truncate_to(&self, components: usize) -> Box<dyn VersionInner> {
+        if components < 2 {
+            Box::new(VersionSingleton {
+                has_prefix: self.has_prefix,
+                major: self.major,
+            })
+        } else {
+            self.dupe()
+        }
+    }
 }
 
 impl Display for VersionPair {
@@ -202,18 +568,39 @@ impl Display for VersionPair {
 #[derive(Debug)]
 struct VersionTriple {
     has_prefix: bool,
-    major: i32,
-    minor: i32,
-    build: i32,
+    major: u64,
+    minor: u64,
+    build: u64,
 }
 
 impl VersionInner for VersionTriple {
+    fn has_prefix(&self) -> bool {
+        self.has_prefix
+    }
+
     fn set_prefix(&mut self, value: bool) {
         self.has_prefix = value;
     }
 
-    fn increment(&mut self) {
-        self.build += 1;
+    fn components(&self) -> Vec<u64> {
+        vec![self.major, self.minor, self.build]
+    }
+
+    fn increment_at(&mut self, index: usize) -> VersionParseResult<()> {
+        match index {
+            0 => {
+                self.major = checked_increment(self.major, self)?;
+                self.minor = 0;
+                self.build = 0;
+            }
+            1 => {
+                self.minor = checked_increment(self.minor, self)?;
+                self.build = 0;
+            }
+            2 => self.build = checked_increment(self.build, self)?,
+            _ => return Err(invalid_index(self, index)),
+        }
+        Ok(())
     }
 
     fn dupe(&self) -> Box<dyn VersionInner> {
@@ -224,6 +611,21 @@ impl VersionInner for VersionTriple {
             build: self.build,
         })
     }
+
+    fn truncate_to(&self, components: usize) -> Box<dyn VersionInner> {
+        match components {
+            0 | 1 => Box::new(VersionSingleton {
+                has_prefix: self.has_prefix,
+                major: self.major,
+            }),
+            2 => Box::new(VersionPair {
+                has_prefix: self.has_prefix,
+                major: self.major,
+                minor: self.minor,
+            }),
+            _ => self.dupe(),
+        }
+    }
 }
 
 impl Display for VersionTriple {
@@ -243,9 +645,11 @@ impl Display for VersionTriple {
 
 #[cfg(test)]
 mod tests {
-    use super::Version;
+    use super::{Version, VersionParseResult};
+    use crate::{Bump, ChangeKind};
     use anyhow::Result;
     use rstest::rstest;
+    use std::cmp::Ordering;
 
     #[rstest]
     #[case("1", "v1", "2", "1")]
@@ -273,9 +677,406 @@ mod tests {
         assert_eq!(version.to_string(), other_version.to_string());
 
         let mut version = input.parse::<Version>()?;
-        version.increment();
+        version.increment()?;
         assert_eq!(expected_incremented, version.to_string());
 
         Ok(())
     }
+
+    #[rstest]
+    #[case("v2147483648")]
+    #[case("4294967296.1.2")]
+    #[case("v18446744073709551615.0")]
+    fn large_components(#[case] input: &str) -> Result<()> {
+        assert_eq!(input, input.parse::<Version>()?.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("v18446744073709551615")]
+    #[case("1.18446744073709551615")]
+    #[case("1.2.18446744073709551615")]
+    fn increment_overflow(#[case] input: &str) -> Result<()> {
+        let mut version = input.parse::<Version>()?;
+        assert!(version.increment().is_err());
+        assert!(input.parse::<Version>()?.next().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn next_at_overflow() -> Result<()> {
+        let version = "18446744073709551615.0.0".parse::<Version>()?;
+        assert!(version.next_at(0).is_err());
+        assert_eq!("18446744073709551615.1.0", version.next_at(1)?.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("v-1.0.0")]
+    #[case("-1")]
+    #[case("1.-2.3")]
+    #[case("18446744073709551616")]
+    fn invalid_components(#[case] input: &str) {
+        assert!(input.parse::<Version>().is_err());
+    }
+
+    #[rstest]
+    #[case(Some(0), "0")]
+    #[case(Some(2_147_483_648), "2147483648")]
+    #[case(None, "-1")]
+    #[case(None, "")]
+    #[case(None, "x")]
+    fn parse_component(#[case] expected_result: Option<u64>, #[case] input: &str) {
+        assert_eq!(expected_result, super::parse_component(input).ok());
+    }
+
+    #[rstest]
+    #[case("-1", "v-1.0.0")]
+    #[case("-1", "-1")]
+    #[case("-2", "1.-2.3")]
+    #[case("-3", "1:1.2.-3-rc.1")]
+    fn negative_components(#[case] expected_component: &str, #[case] input: &str) {
+        let e = input.parse::<Version>().expect_err("must fail");
+        assert_eq!(
+            format!("version component {expected_component} must not be negative"),
+            e.to_string()
+        );
+    }
+
+    #[rstest]
+    #[case("1", "1", 0)]
+    #[case("1", "1", 1)]
+    #[case("v1", "v1", 2)]
+    #[case("1", "1.2", 1)]
+    #[case("v1.2", "v1.2", 2)]
+    #[case("1", "1.2.3", 1)]
+    #[case("v1", "v1.2.3", 1)]
+    #[case("1.2", "1.2.3", 2)]
+    #[case("v1.2", "v1.2.3", 2)]
+    #[case("1.2.3", "1.2.3", 3)]
+    #[case("v1.2.3", "v1.2.3", 4)]
+    fn truncate_to(
+        #[case] expected_result: &str,
+        #[case] input: &str,
+        #[case] components: usize,
+    ) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        assert_eq!(expected_result, version.truncate_to(components).to_string());
+        assert_eq!(input, version.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("2", "1")]
+    #[case("v1.3", "v1.2")]
+    #[case("1.2.4", "1.2.3")]
+    fn next(#[case] expected_result: &str, #[case] input: &str) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        assert_eq!(expected_result, version.next()?.to_string());
+        assert_eq!(input, version.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some("2"), "1", 0)]
+    #[case(None, "1", 1)]
+    #[case(Some("2.0"), "1.2", 0)]
+    #[case(Some("v1.3"), "v1.2", 1)]
+    #[case(None, "1.2", 2)]
+    #[case(Some("2.0.0"), "1.2.3", 0)]
+    #[case(Some("1.3.0"), "1.2.3", 1)]
+    #[case(Some("v1.2.4"), "v1.2.3", 2)]
+    #[case(None, "1.2.3", 3)]
+    #[case(Some("1.3.0"), "1.2.3-rc.1+abc", 1)]
+    fn next_at(
+        #[case] expected_result: Option<&str>,
+        #[case] input: &str,
+        #[case] index: usize,
+    ) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        assert_eq!(
+            expected_result,
+            version
+                .next_at(index)
+                .ok()
+                .map(|v| v.to_string())
+                .as_deref()
+        );
+        assert_eq!(input, version.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Ordering::Equal, "1.2.3", "1.2.3")]
+    #[case(Ordering::Less, "1.2.3", "1.2.4")]
+    #[case(Ordering::Greater, "1.10.0", "1.9.9")]
+    #[case(Ordering::Greater, "2", "1.9.9")]
+    #[case(Ordering::Equal, "1.2", "1.2.0")]
+    #[case(Ordering::Less, "1.2", "1.2.1")]
+    #[case(Ordering::Equal, "1.2.3", "v1.2.3")]
+    #[case(Ordering::Less, "v1.2.3", "1.2.4")]
+    #[case(Ordering::Greater, "v1.10.0", "v1.9.0")]
+    #[case(Ordering::Less, "1.2.3-rc.1", "1.2.3")]
+    #[case(Ordering::Equal, "1.2.3+abc", "1.2.3+def")]
+    fn ordering(
+        #[case] expected_result: Ordering,
+        #[case] lhs: &str,
+        #[case] rhs: &str,
+    ) -> Result<()> {
+        let lhs = lhs.parse::<Version>()?;
+        let rhs = rhs.parse::<Version>()?;
+        assert_eq!(expected_result, lhs.cmp(&rhs));
+        assert_eq!(expected_result.reverse(), rhs.cmp(&lhs));
+        assert_eq!(expected_result == Ordering::Equal, lhs == rhs);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Ordering::Equal, "1.2.3", "1.2.3")]
+    #[case(Ordering::Less, "1.2", "1.2.0")]
+    #[case(Ordering::Less, "1.2.3", "v1.2.3")]
+    #[case(Ordering::Less, "1.2.3", "0:1.2.3")]
+    #[case(Ordering::Less, "1.2.3", "1.2.3+abc")]
+    #[case(Ordering::Less, "1.2.3+abc", "1.2.3+abd")]
+    #[case(Ordering::Less, "v1.2.3", "1.2.4")]
+    #[case(Ordering::Less, "1.2.3-rc.1", "1.2.3")]
+    fn cmp_exact(
+        #[case] expected_result: Ordering,
+        #[case] lhs: &str,
+        #[case] rhs: &str,
+    ) -> Result<()> {
+        let lhs = lhs.parse::<Version>()?;
+        let rhs = rhs.parse::<Version>()?;
+        assert_eq!(expected_result, lhs.cmp_exact(&rhs));
+        assert_eq!(expected_result.reverse(), rhs.cmp_exact(&lhs));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("1.0.0", "0.4.2", Bump::Major, false)]
+    #[case("0.5.0", "0.4.2", Bump::Major, true)]
+    #[case("0.5.0", "0.4.2", Bump::Minor, false)]
+    #[case("0.4.3", "0.4.2", Bump::Minor, true)]
+    #[case("0.4.3", "0.4.2", Bump::Patch, false)]
+    #[case("0.4.3", "0.4.2", Bump::Patch, true)]
+    #[case("v2.0.0", "v1.4.2", Bump::Major, true)]
+    #[case("1.5.0", "1.4.2", Bump::Minor, true)]
+    fn bump(
+        #[case] expected_result: &str,
+        #[case] input: &str,
+        #[case] kind: Bump,
+        #[case] zerover: bool,
+    ) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        assert_eq!(expected_result, version.bump(kind, zerover)?.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some("2.0.0"), Some("1.3.0"), Some("1.2.4"), "1.2.3")]
+    #[case(Some("3.0.0"), Some("2.5.0"), Some("2.4.8"), "2.4.7")]
+    #[case(Some("v1.0.0"), Some("v0.10.0"), Some("v0.9.10"), "v0.9.9")]
+    #[case(Some("v2.0.0"), Some("v1.3.0"), Some("v1.2.4"), "v1.2.3-rc.1+abc")]
+    #[case(Some("1:2.0.0"), Some("1:1.3.0"), Some("1:1.2.4"), "1:1.2.3")]
+    #[case(Some("2.0"), Some("1.3"), None, "1.2")]
+    #[case(Some("v3.0"), Some("v2.5"), None, "v2.4")]
+    #[case(Some("v2"), None, None, "v1")]
+    fn increment_component(
+        #[case] expected_major: Option<&str>,
+        #[case] expected_minor: Option<&str>,
+        #[case] expected_patch: Option<&str>,
+        #[case] input: &str,
+    ) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        let increment = |f: fn(&mut Version) -> VersionParseResult<()>| {
+            let mut version = version.dupe();
+            f(&mut version).map(|()| version.to_string())
+        };
+
+        assert_eq!(
+            expected_major,
+            increment(Version::increment_major).ok().as_deref()
+        );
+        assert_eq!(
+            expected_minor,
+            increment(Version::increment_minor).ok().as_deref()
+        );
+        assert_eq!(
+            expected_patch,
+            increment(Version::increment_patch).ok().as_deref()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn increment_missing_component() -> Result<()> {
+        let mut version = "v1".parse::<Version>()?;
+        let e = version
+            .increment_patch()
+            .expect_err("must fail")
+            .to_string();
+        assert!(e.contains("version v1 has no patch component"));
+        assert_eq!("v1", version.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(1, None, None, "1")]
+    #[case(1, Some(2), None, "v1.2")]
+    #[case(1, Some(2), Some(3), "1.2.3")]
+    #[case(2, Some(3), Some(4), "1:v2.3.4-rc.1+abc")]
+    fn components(
+        #[case] expected_major: u64,
+        #[case] expected_minor: Option<u64>,
+        #[case] expected_patch: Option<u64>,
+        #[case] input: &str,
+    ) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        assert_eq!(expected_major, version.major());
+        assert_eq!(expected_minor, version.minor());
+        assert_eq!(expected_patch, version.patch());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some(1), "1:2.3.4")]
+    #[case(Some(0), "0:v2.3")]
+    #[case(Some(12), "12:5")]
+    #[case(None, "2.3.4")]
+    fn epoch(#[case] expected_result: Option<u64>, #[case] input: &str) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        assert_eq!(expected_result, version.epoch());
+        assert_eq!(input, version.to_string());
+        assert_eq!(input, version.dupe().to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("1:2.3.5", "1:2.3.4")]
+    #[case("2:v1.3", "2:v1.2")]
+    fn epoch_increment(#[case] expected_result: &str, #[case] input: &str) -> Result<()> {
+        let mut version = input.parse::<Version>()?;
+        version.increment()?;
+        assert_eq!(expected_result, version.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("-1:2.3.4")]
+    #[case("x:2.3.4")]
+    #[case("1:")]
+    #[case("1:2:3")]
+    fn epoch_invalid(#[case] input: &str) {
+        assert!(input.parse::<Version>().is_err());
+    }
+
+    #[rstest]
+    #[case(Ordering::Greater, "1:1.0.0", "9.9.9")]
+    #[case(Ordering::Less, "1:9.9.9", "2:0.0.1")]
+    #[case(Ordering::Less, "1:1.2.3", "1:1.2.4")]
+    #[case(Ordering::Equal, "1.2.3", "0:1.2.3")]
+    #[case(Ordering::Less, "0:1.2.3", "1.2.4")]
+    fn epoch_ordering(
+        #[case] expected_result: Ordering,
+        #[case] lhs: &str,
+        #[case] rhs: &str,
+    ) -> Result<()> {
+        let lhs = lhs.parse::<Version>()?;
+        let rhs = rhs.parse::<Version>()?;
+        assert_eq!(expected_result, lhs.cmp(&rhs));
+        assert_eq!(expected_result.reverse(), rhs.cmp(&lhs));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some("rc.1"), None, "1.2.3-rc.1")]
+    #[case(None, Some("abc"), "1.2.3+abc")]
+    #[case(Some("rc.1"), Some("abc"), "1.2.3-rc.1+abc")]
+    #[case(Some("alpha-2"), Some("build.456"), "v1.2.3-alpha-2+build.456")]
+    #[case(Some("rc.1"), None, "1:2.0-rc.1")]
+    #[case(None, None, "1.2.3")]
+    fn suffixes(
+        #[case] expected_prerelease: Option<&str>,
+        #[case] expected_build_metadata: Option<&str>,
+        #[case] input: &str,
+    ) -> Result<()> {
+        let version = input.parse::<Version>()?;
+        assert_eq!(expected_prerelease, version.prerelease());
+        assert_eq!(expected_build_metadata, version.build_metadata());
+        assert_eq!(input, version.to_string());
+        assert_eq!(input, version.dupe().to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("1.2.3-")]
+    #[case("1.2.3+")]
+    #[case("1.2.3-rc..1")]
+    #[case("1.2.3+abc_def")]
+    #[case("1.2.3-rc.1+")]
+    #[case("-rc.1")]
+    fn suffixes_invalid(#[case] input: &str) {
+        assert!(input.parse::<Version>().is_err());
+    }
+
+    #[rstest]
+    #[case("1.2.4", "1.2.3-rc.1")]
+    #[case("1.2.4", "1.2.3+abc")]
+    #[case("v1.2.4", "v1.2.3-rc.1+abc")]
+    fn suffixes_increment(#[case] expected_result: &str, #[case] input: &str) -> Result<()> {
+        let mut version = input.parse::<Version>()?;
+        version.increment()?;
+        assert_eq!(expected_result, version.to_string());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Ordering::Less, "1.2.3-rc.1", "1.2.3")]
+    #[case(Ordering::Greater, "1.2.3-rc.1", "1.2.2")]
+    #[case(Ordering::Less, "1.2.3-alpha", "1.2.3-alpha.1")]
+    #[case(Ordering::Less, "1.2.3-alpha.1", "1.2.3-alpha.beta")]
+    #[case(Ordering::Less, "1.2.3-beta.2", "1.2.3-beta.11")]
+    #[case(Ordering::Less, "1.2.3-beta.11", "1.2.3-rc.1")]
+    #[case(Ordering::Equal, "1.2.3+abc", "1.2.3+abd")]
+    #[case(Ordering::Equal, "1.2.3", "1.2.3+abc")]
+    #[case(Ordering::Equal, "1.2.3-rc.1+abc", "1.2.3-rc.1+abc")]
+    fn suffixes_ordering(
+        #[case] expected_result: Ordering,
+        #[case] lhs: &str,
+        #[case] rhs: &str,
+    ) -> Result<()> {
+        let lhs = lhs.parse::<Version>()?;
+        let rhs = rhs.parse::<Version>()?;
+        assert_eq!(expected_result, lhs.cmp(&rhs));
+        assert_eq!(expected_result.reverse(), rhs.cmp(&lhs));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(ChangeKind::Major, "1.4.2", "2.0.0")]
+    #[case(ChangeKind::Minor, "1.4.2", "1.5.0")]
+    #[case(ChangeKind::Patch, "1.4.2", "1.4.3")]
+    #[case(ChangeKind::None, "1.4.2", "1.4.2")]
+    #[case(ChangeKind::None, "v1.4.2", "1.4.2")]
+    #[case(ChangeKind::None, "1.4", "1.4.0")]
+    #[case(ChangeKind::Patch, "1.4", "1.4.1")]
+    #[case(ChangeKind::Minor, "1", "1.1")]
+    #[case(ChangeKind::Downgrade, "1.4.2", "1.4.1")]
+    #[case(ChangeKind::Downgrade, "1.4.2", "0.9.9")]
+    #[case(ChangeKind::Downgrade, "1.4.1", "1.4")]
+    #[case(ChangeKind::Major, "9.9.9", "1:1.0.0")]
+    #[case(ChangeKind::Downgrade, "2:1.0.0", "1:2.0.0")]
+    fn change_kind(
+        #[case] expected_result: ChangeKind,
+        #[case] older: &str,
+        #[case] newer: &str,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result,
+            older
+                .parse::<Version>()?
+                .change_kind(&newer.parse::<Version>()?)
+        );
+        Ok(())
+    }
 }
@@ -19,15 +19,24 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
-use crate::{constants::CONFIG_FILE_NAME, serialization::Config};
-use anyhow::Result;
+use crate::constants::{CONFIG_FILE_NAME, GLOBAL_CONFIG_FILE_NAME};
+use crate::error::DevtoolError;
+use crate::reporter::Reporter;
+use crate::serialization::Config;
+use crate::text_file::read_yaml_file;
+use anyhow::{anyhow, Result};
 use devtool_git::Git;
-use joatmon::{read_yaml_file, safe_write_file};
-use std::path::PathBuf;
+use joatmon::safe_write_file;
+use std::env::var_os;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
 pub struct App {
     pub git: Git,
+    pub start_dir: PathBuf,
+    pub config_search_stop: Option<PathBuf>,
+    pub global_config: bool,
+    pub reporter: Reporter,
 }
 
 impl App {
@@ -35,8 +44,13 @@ impl App {
     where
         P: Into<PathBuf>,
     {
+        let git_dir = git_dir.into();
         Self {
+            start_dir: git_dir.clone(),
             git: Git::new(git_dir),
+            config_search_stop: None,
+            global_config: false,
+            reporter: Reporter::default(),
         }
     }
 
@@ -44,22 +58,106 @@ impl App {
         self.git.dir.join(CONFIG_FILE_NAME)
     }
 
+    pub fn find_config_path(&self) -> Option<PathBuf> {
+        let stop_dir = self.config_search_stop.as_deref().unwrap_or(&self.git.dir);
+        find_config(&self.start_dir, stop_dir).or_else(|| {
+            if self.global_config {
+                global_config_path().filter(|p| p.is_file())
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn read_config(&self) -> Result<Option<Config>> {
         // TBD: Complete with time-of-check time-of-use race condition!
-        let config_path = self.config_path();
-        if config_path.is_file() {
-            Ok(Some(read_yaml_file(&config_path)?))
-        } else {
-            Ok(None)
+        match self.find_config_path() {
+            Some(config_path) => Ok(Some(read_yaml_file(&config_path).map_err(|e| {
+                DevtoolError::Config(anyhow!(
+                    "Invalid configuration file {}: {e}",
+                    config_path.display()
+                ))
+            })?)),
+            None => Ok(None),
         }
     }
 
-    pub fn write_config(&self, config: &Config, overwrite: bool) -> Result<()> {
-        safe_write_file(
-            &self.config_path(),
-            serde_yaml::to_string(config)?,
-            overwrite,
-        )?;
+    pub fn write_config(&self, content: &str, overwrite: bool) -> Result<()> {
+        safe_write_file(&self.config_path(), content, overwrite)?;
+        Ok(())
+    }
+}
+
+fn find_config(start_dir: &Path, stop_dir: &Path) -> Option<PathBuf> {
+    let start_dir = if start_dir.starts_with(stop_dir) {
+        start_dir
+    } else {
+        stop_dir
+    };
+
+    start_dir
+        .ancestors()
+        .take_while(|d| d.starts_with(stop_dir))
+        .map(|d| d.join(CONFIG_FILE_NAME))
+        .find(|p| p.is_file())
+}
+
+fn global_config_path() -> Option<PathBuf> {
+    let config_dir = if cfg!(windows) {
+        var_os("APPDATA").map(PathBuf::from)
+    } else {
+        var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var_os("HOME").map(|d| Path::new(&d).join(".config")))
+    }?;
+    Some(config_dir.join("devtool").join(GLOBAL_CONFIG_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_config;
+    use crate::constants::CONFIG_FILE_NAME;
+    use crate::test_dir::TestDir;
+    use anyhow::Result;
+    use rstest::rstest;
+    use std::fs::{create_dir_all, write};
+    use std::path::Path;
+
+    #[rstest]
+    #[case(Some("repo/sub"), "repo/sub/deeper", "repo")]
+    #[case(Some("repo/sub"), "repo/sub", "repo")]
+    #[case(Some("repo"), "repo/other", "repo")]
+    #[case(Some("repo"), "repo", "repo")]
+    #[case(None, "plain/sub", "plain")]
+    #[case(Some("repo"), "elsewhere", "repo")]
+    fn find_config_nested(
+        #[case] expected_result: Option<&str>,
+        #[case] start_dir: &str,
+        #[case] stop_dir: &str,
+    ) -> Result<()> {
+        let test_dir = TestDir::new(&format!("find-config-{}", start_dir.replace('/', "-")))?;
+        let dir = test_dir.path();
+        for d in ["repo/sub/deeper", "repo/other", "plain/sub", "elsewhere"] {
+            create_dir_all(dir.join(d))?;
+        }
+        for d in [".", "repo", "repo/sub"] {
+            write(dir.join(d).join(CONFIG_FILE_NAME), "")?;
+        }
+
+        let result = find_config(&dir.join(start_dir), &dir.join(stop_dir));
+
+        assert_eq!(
+            expected_result.map(|d| dir.join(d).join(CONFIG_FILE_NAME)),
+            result
+        );
         Ok(())
     }
+
+    #[test]
+    fn find_config_outside_stop_dir() {
+        assert_eq!(
+            None,
+            find_config(Path::new("/nonexistent/a"), Path::new("/nonexistent/b"))
+        );
+    }
 }
@@ -19,8 +19,14 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
-use clap::{ArgAction, Parser, Subcommand};
-use devtool_version::Version;
+use crate::dirty_tree_policy::DirtyTreePolicy;
+use crate::logging::TimeFormat;
+use crate::paths::normalize_path;
+use chrono::{DateTime, FixedOffset};
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use devtool_git::{DEFAULT_PUSH_RETRIES, DEFAULT_TIMEOUT_SECS};
+use devtool_version::{Bump, Version};
 use log::LevelFilter;
 use path_absolutize::Absolutize;
 use std::path::PathBuf;
@@ -32,6 +38,7 @@ const PACKAGE_HOME_PAGE: &str = env!("CARGO_PKG_HOMEPAGE");
 const PACKAGE_BUILD_VERSION: Option<&str> = option_env!("RUST_TOOL_ACTION_BUILD_VERSION");
 
 #[derive(Parser, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 #[command(
     name = PACKAGE_NAME,
     version = PACKAGE_VERSION,
@@ -51,53 +58,733 @@ pub struct Args {
     )]
     pub log_level: LevelFilter,
 
+    #[arg(
+        global = true,
+        help = "Only log warnings and errors",
+        short = 'q',
+        long = "quiet",
+        conflicts_with = "log_level"
+    )]
+    pub quiet: bool,
+
+    #[arg(
+        global = true,
+        help = "Logging output format",
+        long = "log-format",
+        value_enum,
+        default_value_t = LogFormat::Console
+    )]
+    pub log_format: LogFormat,
+
+    #[arg(
+        global = true,
+        help = "Format of log entry timestamps: rfc3339, epoch-ms or custom:FMT using strftime syntax",
+        long = "log-time-format",
+        default_value = "rfc3339"
+    )]
+    pub log_time_format: TimeFormat,
+
+    #[arg(
+        global = true,
+        help = "Append log entries to file in addition to standard output",
+        long = "log-file",
+        value_parser = parse_absolute_path
+    )]
+    pub log_file: Option<PathBuf>,
+
     #[arg(global = true, help = "Path to Git repository", short = 'd', long = "dir", value_parser = parse_absolute_path)]
     pub git_dir: Option<PathBuf>,
 
+    #[arg(
+        global = true,
+        help = "Operate on a plain project directory without requiring a Git repository",
+        long = "no-git"
+    )]
+    pub no_git: bool,
+
+    #[arg(
+        global = true,
+        help = "Print each Git command line to stderr before running it",
+        long = "print-git-commands"
+    )]
+    pub print_git_commands: bool,
+
+    #[arg(
+        global = true,
+        help = "Number of times to retry a push that fails with a network error",
+        long = "push-retries",
+        default_value_t = DEFAULT_PUSH_RETRIES
+    )]
+    pub push_retries: u32,
+
+    #[arg(
+        global = true,
+        help = "Number of seconds after which a Git command is killed",
+        long = "git-timeout",
+        default_value_t = DEFAULT_TIMEOUT_SECS
+    )]
+    pub git_timeout: u64,
+
+    #[arg(global = true, help = "Treat warnings as errors", long = "strict")]
+    pub strict: bool,
+
+    #[arg(global = true, help = "Disable colored output", long = "no-color")]
+    pub no_color: bool,
+
+    #[arg(
+        global = true,
+        help = "Directory at which upward configuration file search stops (defaults to Git repository root)",
+        long = "config-search-stop",
+        value_parser = parse_absolute_path
+    )]
+    pub config_search_stop: Option<PathBuf>,
+
+    #[arg(
+        global = true,
+        help = "Also consult configuration file in user configuration directory",
+        long = "global-config"
+    )]
+    pub global_config: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
 #[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Command {
     #[command(
         name = "bump-version",
-        about = "Update Cargo.toml/pyproject.toml version, generate new Git tag and push"
+        about = "Update Cargo.toml/pyproject.toml/gradle.properties version, generate new Git tag and push"
     )]
     BumpVersion {
-        #[arg(help = "Version number to bump to")]
+        #[arg(help = "Version number to bump to (overrides DEVTOOL_VERSION environment variable)")]
         version: Option<Version>,
 
-        #[arg(help = "Do not push commits and tags", long = "no-push-all", action = ArgAction::SetFalse)]
-        push_all: bool,
+        #[arg(
+            help = "Increment major component of most recent version",
+            long = "major",
+            group = "bump",
+            conflicts_with = "version"
+        )]
+        major: bool,
 
         #[arg(
-            help = "Push commits and tags",
+            help = "Increment minor component of most recent version",
+            long = "minor",
+            group = "bump",
+            conflicts_with = "version"
+        )]
+        minor: bool,
+
+        #[arg(
+            help = "Increment patch component of most recent version",
+            long = "patch",
+            group = "bump",
+            conflicts_with = "version"
+        )]
+        patch: bool,
+
+        #[arg(
+            help = "Follow SemVer 0.x rules while major version is 0: --major bumps minor and --minor bumps patch",
+            long = "zerover",
+            overrides_with = "no_zerover"
+        )]
+        zerover: bool,
+
+        #[arg(
+            help = "Always bump the requested component even while major version is 0",
+            long = "no-zerover",
+            overrides_with = "zerover"
+        )]
+        no_zerover: bool,
+
+        #[arg(
+            help = "Push commits and tags (default unless push is disabled in config)",
             long = "push-all",
+            overrides_with = "no_push_all"
+        )]
+        push_all: bool,
+
+        #[arg(
+            help = "Do not push commits and tags",
+            long = "no-push-all",
             overrides_with = "push_all"
         )]
-        _no_push_all: bool,
+        no_push_all: bool,
+
+        #[arg(
+            help = "Push tags only and leave the release commit to be pushed separately",
+            long = "tags-only",
+            conflicts_with_all = ["no_push_all", "push_dry_run"]
+        )]
+        tags_only: bool,
+
+        #[arg(
+            help = "Create commit and tag locally and then check that push would succeed without updating remote",
+            long = "push-dry-run"
+        )]
+        push_dry_run: bool,
+
+        #[arg(
+            help = "Behaviour when there are no commits since the most recent tag",
+            long = "on-no-commits",
+            value_enum,
+            default_value_t = OnNoCommits::Error
+        )]
+        on_no_commits: OnNoCommits,
+
+        #[arg(
+            help = "Behaviour when manifest versions do not match most recent tag",
+            long = "on-drift",
+            value_enum,
+            default_value_t = OnDrift::Warn
+        )]
+        on_drift: OnDrift,
+
+        #[arg(
+            help = "Behaviour when no manifests are found",
+            long = "on-no-manifests",
+            value_enum,
+            default_value_t = OnNoManifests::TagOnly
+        )]
+        on_no_manifests: OnNoManifests,
+
+        #[arg(
+            help = "Template for tag name using {version}, {major}, {minor}, {patch} and {prefix} placeholders",
+            long = "tag-template"
+        )]
+        tag_template: Option<String>,
+
+        #[arg(
+            help = "Glob pattern selecting tags considered when computing next version (defaults to pattern derived from tag template)",
+            long = "match"
+        )]
+        tag_match: Option<String>,
+
+        #[arg(
+            help = "Behaviour when the Git working directory is not clean (default: error)",
+            long = "dirty-tree-policy",
+            value_enum
+        )]
+        dirty_tree_policy: Option<DirtyTreePolicy>,
+
+        #[arg(
+            help = "How to update Cargo.lock (default: update for library crates, build otherwise)",
+            long = "lock-strategy",
+            value_enum
+        )]
+        lock_strategy: Option<LockStrategy>,
+
+        #[arg(
+            help = "Read tag annotation message from file",
+            long = "tag-message-file",
+            value_parser = parse_absolute_path
+        )]
+        tag_message_file: Option<PathBuf>,
+
+        #[arg(
+            help = "Skip release if no tracked file has changed since most recent tag",
+            long = "only-if-changed"
+        )]
+        only_if_changed: bool,
+
+        #[arg(
+            help = "Prefix tag with \"v\" regardless of most recent tag",
+            long = "tag-prefix",
+            overrides_with = "no_tag_prefix"
+        )]
+        tag_prefix: bool,
+
+        #[arg(
+            help = "Do not prefix tag with \"v\" regardless of most recent tag",
+            long = "no-tag-prefix",
+            visible_alias = "no-prefix",
+            overrides_with = "tag_prefix"
+        )]
+        no_tag_prefix: bool,
+
+        #[arg(
+            help = "Version bump commit message template containing {version} placeholder",
+            long = "message"
+        )]
+        commit_message_template: Option<String>,
+
+        #[arg(help = "Create signed tag", long = "sign")]
+        sign: bool,
+
+        #[arg(help = "Sign version bump commit", long = "sign-commit")]
+        sign_commit: bool,
+
+        #[arg(
+            help = "Author and committer date of version bump commit in RFC 3339 format for reproducible releases",
+            long = "date",
+            value_parser = parse_date
+        )]
+        commit_date: Option<DateTime<FixedOffset>>,
+
+        #[arg(
+            help = "Create GitHub release after pushing tag (requires GITHUB_TOKEN environment variable)",
+            long = "github-release"
+        )]
+        github_release: bool,
+
+        #[arg(
+            help = "Dotted key path pattern excluded from version_keys updates (may be repeated)",
+            long = "exclude-pattern"
+        )]
+        exclude_patterns: Vec<String>,
+
+        #[arg(
+            help = "Branch name or glob pattern from which releases may be made (may be repeated, default: main and master)",
+            long = "branch"
+        )]
+        allowed_branches: Vec<String>,
+
+        #[arg(
+            help = "Do not warn when the config file has uncommitted changes",
+            long = "allow-dirty-config"
+        )]
+        allow_dirty_config: bool,
+
+        #[arg(
+            help = "Print paths of files that would be updated and exit without changing anything",
+            long = "list-files"
+        )]
+        list_files: bool,
+
+        #[arg(
+            help = "Run all checks and print the new version, files and tag without changing anything",
+            long = "dry-run"
+        )]
+        dry_run: bool,
+
+        #[arg(
+            help = "Prepend section generated from conventional commits since most recent tag to CHANGELOG.md",
+            long = "changelog"
+        )]
+        changelog: bool,
+
+        #[arg(
+            help = "Do not treat untracked files as making the working directory dirty",
+            long = "ignore-untracked"
+        )]
+        ignore_untracked: bool,
+
+        #[arg(
+            help = "Maximum directory depth below project directory to search for manifests (default: unlimited)",
+            long = "max-depth"
+        )]
+        max_depth: Option<usize>,
+
+        #[arg(
+            help = "Fetch tags from origin and fail if the new tag already exists on the remote",
+            long = "fetch"
+        )]
+        fetch: bool,
+
+        #[arg(
+            help = "Skip pre-commit and commit-msg hooks when committing version bump",
+            long = "no-verify"
+        )]
+        no_verify: bool,
+    },
+
+    #[command(
+        name = "check-manifests",
+        about = "Check that manifests have consistent versions in canonical form without changing them"
+    )]
+    CheckManifests,
+
+    #[command(
+        name = "completions",
+        about = "Print shell completion script to standard output"
+    )]
+    Completions {
+        #[arg(help = "Shell", value_enum)]
+        shell: Shell,
     },
 
     #[command(name = "gen-config", about = "Generate devtool configuration file")]
-    GenerateConfig,
+    GenerateConfig {
+        #[arg(
+            help = "Print configuration to standard output instead of writing file",
+            long = "dry-run"
+        )]
+        dry_run: bool,
+    },
 
     #[command(name = "gen-ignore", about = "Generate .gitignore file")]
-    GenerateIgnore,
+    GenerateIgnore {
+        #[arg(
+            help = "Write .gitignore file instead of printing to standard output, appending only new entries to an existing file",
+            long = "write"
+        )]
+        write: bool,
+
+        #[arg(
+            help = "Overwrite existing .gitignore file",
+            long = "force",
+            requires = "write",
+            conflicts_with = "append"
+        )]
+        force: bool,
+
+        #[arg(
+            help = "Update only the section of existing .gitignore file managed by devtool",
+            long = "append",
+            requires = "write"
+        )]
+        append: bool,
+
+        #[arg(
+            help = "Include standard entries for detected project types such as Rust, Python and Node",
+            long = "templates"
+        )]
+        templates: bool,
+    },
+
+    #[command(
+        name = "list-ecosystems",
+        about = "List supported ecosystems and those detected in the project"
+    )]
+    ListEcosystems,
+
+    #[command(
+        name = "list-tags",
+        about = "List tags sorted by version followed by tags that are not versions"
+    )]
+    ListTags,
+
+    #[command(
+        name = "prune-tags",
+        about = "Delete local tags that no longer exist on the remote"
+    )]
+    PruneTags {
+        #[arg(
+            help = "Remote to compare tags against",
+            long = "remote",
+            default_value = "origin"
+        )]
+        remote: String,
+
+        #[arg(help = "Delete tags instead of just listing them", long = "confirm")]
+        confirm: bool,
+    },
+
+    #[command(
+        name = "resume",
+        about = "Continue or roll back a bump-version run that did not complete"
+    )]
+    Resume {
+        #[arg(help = "Undo completed steps instead of continuing", long = "rollback")]
+        rollback: bool,
+    },
 
     #[command(name = "scratch", about = "(Experimental)")]
     Scratch,
 
+    #[command(
+        name = "undo-bump",
+        about = "Delete most recent tag and remove its version bump commit if neither has been pushed"
+    )]
+    UndoBump,
+
     #[command(
         name = "show-description",
         about = "Show Git description and commit information"
     )]
-    ShowDescription,
+    ShowDescription {
+        #[arg(
+            help = "Behaviour when the Git working directory is not clean (default: ignore)",
+            long = "dirty-tree-policy",
+            value_enum
+        )]
+        dirty_tree_policy: Option<DirtyTreePolicy>,
+
+        #[arg(
+            help = "Show number of commits per author since tag",
+            long = "contributors"
+        )]
+        contributors: bool,
+
+        #[arg(
+            help = "Show commit that last changed the version in each manifest",
+            long = "verbose"
+        )]
+        verbose: bool,
+
+        #[arg(
+            help = "Print description and next version as JSON",
+            long = "json",
+            conflicts_with_all = ["contributors", "verbose"]
+        )]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum LockStrategy {
+    #[value(help = "Run cargo build")]
+    Build,
+
+    #[value(help = "Run cargo update scoped to the package")]
+    Update,
+
+    #[value(help = "Do not update Cargo.lock")]
+    Skip,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum LogFormat {
+    #[value(help = "Colored human-readable line per entry")]
+    Console,
+
+    #[value(help = "One JSON object per line")]
+    Json,
+
+    #[value(help = "Single JSON array of all entries emitted at exit")]
+    JsonArray,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OnDrift {
+    #[value(help = "Fail with an error")]
+    Error,
+
+    #[value(help = "Print a warning and continue")]
+    Warn,
+
+    #[value(help = "Do not compare manifest versions with most recent tag")]
+    Ignore,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OnNoManifests {
+    #[value(help = "Create the tag without changing any files")]
+    TagOnly,
+
+    #[value(help = "Fail with an error")]
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OnNoCommits {
+    #[value(help = "Fail with an error")]
+    Error,
+
+    #[value(help = "Exit successfully without bumping the version")]
+    Skip,
+
+    #[value(help = "Bump the version anyway")]
+    Force,
+}
+
+impl Command {
+    pub const fn requires_git(&self) -> bool {
+        match self {
+            Self::BumpVersion { .. }
+            | Self::CheckManifests
+            | Self::GenerateIgnore { .. }
+            | Self::ListTags
+            | Self::PruneTags { .. }
+            | Self::Resume { .. }
+            | Self::ShowDescription { .. }
+            | Self::UndoBump => true,
+            Self::Completions { .. }
+            | Self::GenerateConfig { .. }
+            | Self::ListEcosystems
+            | Self::Scratch => false,
+        }
+    }
+}
+
+pub const fn bump_kind(major: bool, minor: bool, patch: bool) -> Option<Bump> {
+    if major {
+        Some(Bump::Major)
+    } else if minor {
+        Some(Bump::Minor)
+    } else if patch {
+        Some(Bump::Patch)
+    } else {
+        None
+    }
+}
+
+// Maps a --flag/--no-flag pair onto an optional override
+pub const fn flag_pair(enable: bool, disable: bool) -> Option<bool> {
+    if enable {
+        Some(true)
+    } else if disable {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn parse_date(s: &str) -> Result<DateTime<FixedOffset>, String> {
+    DateTime::parse_from_rfc3339(s).map_err(|e| format!("invalid RFC 3339 date: {e}"))
 }
 
 fn parse_absolute_path(s: &str) -> Result<PathBuf, String> {
     PathBuf::from(s)
         .absolutize()
         .map_err(|_| String::from("invalid path"))
-        .map(|x| x.to_path_buf())
+        .map(|x| normalize_path(&x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bump_kind, flag_pair, Args, Command};
+    use clap::error::ErrorKind;
+    use clap::Parser;
+    use clap_complete::Shell;
+    use devtool_version::Bump;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(true, Command::GenerateIgnore {
+        write: false,
+        force: false,
+        append: false,
+        templates: false
+    })]
+    #[case(true, Command::ShowDescription {
+        dirty_tree_policy: None,
+        contributors: false,
+        verbose: false,
+        json: false
+    })]
+    #[case(true, Command::CheckManifests)]
+    #[case(true, Command::Resume { rollback: false })]
+    #[case(true, Command::UndoBump)]
+    #[case(true, Command::ListTags)]
+    #[case(false, Command::Completions { shell: Shell::Bash })]
+    #[case(false, Command::GenerateConfig { dry_run: false })]
+    #[case(false, Command::ListEcosystems)]
+    #[case(false, Command::Scratch)]
+    fn requires_git(#[case] expected_result: bool, #[case] command: Command) {
+        assert_eq!(expected_result, command.requires_git());
+    }
+
+    #[rstest]
+    #[case(None, &[])]
+    #[case(Some(Bump::Major), &["--major"])]
+    #[case(Some(Bump::Minor), &["--minor"])]
+    #[case(Some(Bump::Patch), &["--patch"])]
+    fn bump_flags(#[case] expected_result: Option<Bump>, #[case] flags: &[&str]) {
+        let args = Args::try_parse_from(["devtool", "bump-version"].iter().chain(flags))
+            .expect("must parse");
+        let Command::BumpVersion {
+            major,
+            minor,
+            patch,
+            ..
+        } = args.command
+        else {
+            panic!("must be bump-version")
+        };
+        assert_eq!(expected_result, bump_kind(major, minor, patch));
+    }
+
+    #[rstest]
+    #[case(&["--major", "--minor"])]
+    #[case(&["--minor", "--patch"])]
+    #[case(&["--patch", "1.2.3"])]
+    #[case(&["1.2.3", "--major"])]
+    fn bump_flags_conflict(#[case] flags: &[&str]) {
+        let e = Args::try_parse_from(["devtool", "bump-version"].iter().chain(flags))
+            .expect_err("must fail");
+        assert_eq!(ErrorKind::ArgumentConflict, e.kind());
+    }
+
+    #[rstest]
+    #[case(true, "2024-01-02T03:04:05Z")]
+    #[case(true, "2024-01-02T03:04:05+01:00")]
+    #[case(false, "2024-01-02")]
+    #[case(false, "yesterday")]
+    fn date_flag(#[case] expected_ok: bool, #[case] date: &str) {
+        assert_eq!(
+            expected_ok,
+            Args::try_parse_from(["devtool", "bump-version", "--date", date]).is_ok()
+        );
+    }
+
+    #[rstest]
+    #[case(true, &["--tags-only"])]
+    #[case(true, &["--push-all", "--tags-only"])]
+    #[case(false, &["--no-push-all", "--tags-only"])]
+    #[case(false, &["--push-dry-run", "--tags-only"])]
+    fn tags_only_flag(#[case] expected_ok: bool, #[case] flags: &[&str]) {
+        assert_eq!(
+            expected_ok,
+            Args::try_parse_from(["devtool", "bump-version"].iter().chain(flags)).is_ok()
+        );
+    }
+
+    #[test]
+    fn quiet_conflicts_with_level() {
+        let e = Args::try_parse_from(["devtool", "--quiet", "--level", "debug", "list-ecosystems"])
+            .expect_err("must fail");
+        assert_eq!(ErrorKind::ArgumentConflict, e.kind());
+    }
+
+    #[rstest]
+    #[case(None, &[])]
+    #[case(Some(true), &["--push-all"])]
+    #[case(Some(false), &["--no-push-all"])]
+    #[case(Some(true), &["--no-push-all", "--push-all"])]
+    #[case(Some(false), &["--push-all", "--no-push-all"])]
+    fn push_all_flags(#[case] expected_result: Option<bool>, #[case] flags: &[&str]) {
+        let args = Args::try_parse_from(["devtool", "bump-version"].iter().chain(flags))
+            .expect("must parse");
+        let Command::BumpVersion {
+            push_all,
+            no_push_all,
+            ..
+        } = args.command
+        else {
+            panic!("must be bump-version")
+        };
+        assert_eq!(expected_result, flag_pair(push_all, no_push_all));
+    }
+
+    #[rstest]
+    #[case(None, &[])]
+    #[case(Some(true), &["--tag-prefix"])]
+    #[case(Some(false), &["--no-tag-prefix"])]
+    #[case(Some(false), &["--no-prefix"])]
+    #[case(Some(true), &["--no-prefix", "--tag-prefix"])]
+    fn tag_prefix_flags(#[case] expected_result: Option<bool>, #[case] flags: &[&str]) {
+        let args = Args::try_parse_from(["devtool", "bump-version"].iter().chain(flags))
+            .expect("must parse");
+        let Command::BumpVersion {
+            tag_prefix,
+            no_tag_prefix,
+            ..
+        } = args.command
+        else {
+            panic!("must be bump-version")
+        };
+        assert_eq!(expected_result, flag_pair(tag_prefix, no_tag_prefix));
+    }
+
+    #[rstest]
+    #[case(None, &[])]
+    #[case(Some(true), &["--zerover"])]
+    #[case(Some(false), &["--no-zerover"])]
+    #[case(Some(true), &["--no-zerover", "--zerover"])]
+    fn zerover_flags(#[case] expected_result: Option<bool>, #[case] flags: &[&str]) {
+        let args = Args::try_parse_from(["devtool", "bump-version"].iter().chain(flags))
+            .expect("must parse");
+        let Command::BumpVersion {
+            zerover,
+            no_zerover,
+            ..
+        } = args.command
+        else {
+            panic!("must be bump-version")
+        };
+        assert_eq!(expected_result, flag_pair(zerover, no_zerover));
+    }
 }
@@ -0,0 +1,252 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use anyhow::Result;
+use chrono::NaiveDate;
+use joatmon::safe_write_file;
+use std::fs::read_to_string;
+use std::path::Path;
+
+const TITLE: &str = "# Changelog\n\n";
+const OTHER_HEADING: &str = "Other";
+
+// Conventional commit types in the order their headings are rendered
+const HEADINGS: [(&str, &str); 11] = [
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "Continuous Integration"),
+    ("style", "Style"),
+    ("revert", "Reverts"),
+    ("chore", "Chores"),
+];
+
+#[derive(Debug, PartialEq)]
+struct Entry<'a> {
+    kind: Option<&'a str>,
+    scope: Option<&'a str>,
+    breaking: bool,
+    description: &'a str,
+}
+
+impl<'a> Entry<'a> {
+    // Parses "type(scope)!: description" falling back to an untyped entry
+    fn parse(subject: &'a str) -> Self {
+        let untyped = Self {
+            kind: None,
+            scope: None,
+            breaking: false,
+            description: subject.trim(),
+        };
+
+        let Some((prefix, description)) = subject.split_once(": ") else {
+            return untyped;
+        };
+
+        let (prefix, breaking) = prefix
+            .strip_suffix('!')
+            .map_or((prefix, false), |p| (p, true));
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, rest)) => match rest.strip_suffix(')') {
+                Some(scope) if !scope.is_empty() => (kind, Some(scope)),
+                _ => return untyped,
+            },
+            None => (prefix, None),
+        };
+
+        if !HEADINGS.iter().any(|(k, _)| *k == kind) {
+            return untyped;
+        }
+
+        Self {
+            kind: Some(kind),
+            scope,
+            breaking,
+            description: description.trim(),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut s = String::from("- ");
+        if self.breaking {
+            s.push_str("**BREAKING** ");
+        }
+        if let Some(scope) = self.scope {
+            s.push_str("**");
+            s.push_str(scope);
+            s.push_str(":** ");
+        }
+        s.push_str(self.description);
+        s
+    }
+}
+
+pub fn render_section<S>(version: &str, date: NaiveDate, subjects: &[S]) -> String
+where
+    S: AsRef<str>,
+{
+    let entries = subjects
+        .iter()
+        .map(|s| Entry::parse(s.as_ref()))
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![format!("## {version} - {}", date.format("%Y-%m-%d"))];
+    let headings = HEADINGS
+        .iter()
+        .map(|(kind, heading)| (Some(*kind), *heading))
+        .chain([(None, OTHER_HEADING)]);
+    for (kind, heading) in headings {
+        let group = entries
+            .iter()
+            .filter(|e| e.kind == kind)
+            .map(Entry::render)
+            .collect::<Vec<_>>();
+        if !group.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("### {heading}"));
+            lines.push(String::new());
+            lines.extend(group);
+        }
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+// Inserts the section above the most recent release, keeping any title and
+// introductory text at the top of the file
+pub fn prepend_section(existing: &str, section: &str) -> String {
+    if existing.trim().is_empty() {
+        return format!("{TITLE}{section}");
+    }
+
+    let mut offset = 0;
+    for line in existing.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            let (before, after) = existing.split_at(offset);
+            return format!("{before}{section}\n{after}");
+        }
+        offset += line.len();
+    }
+
+    if existing.ends_with("\n\n") {
+        format!("{existing}{section}")
+    } else if existing.ends_with('\n') {
+        format!("{existing}\n{section}")
+    } else {
+        format!("{existing}\n\n{section}")
+    }
+}
+
+pub fn update_changelog(path: &Path, section: &str) -> Result<()> {
+    let existing = if path.is_file() {
+        read_to_string(path)?
+    } else {
+        String::new()
+    };
+    safe_write_file(path, prepend_section(&existing, section), true)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prepend_section, render_section, Entry};
+    use chrono::NaiveDate;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Some("feat"), None, false, "add thing", "feat: add thing")]
+    #[case(
+        Some("fix"),
+        Some("git"),
+        false,
+        "handle error",
+        "fix(git): handle error"
+    )]
+    #[case(Some("feat"), Some("api"), true, "drop v1", "feat(api)!: drop v1")]
+    #[case(Some("chore"), None, true, "bump MSRV", "chore!: bump MSRV")]
+    #[case(None, None, false, "Update README", "Update README")]
+    #[case(None, None, false, "wip: stuff", "wip: stuff")]
+    #[case(None, None, false, "feat(): nothing", "feat(): nothing")]
+    #[case(None, None, false, "feat:no space", "feat:no space")]
+    fn entry_parse(
+        #[case] kind: Option<&str>,
+        #[case] scope: Option<&str>,
+        #[case] breaking: bool,
+        #[case] description: &str,
+        #[case] subject: &str,
+    ) {
+        assert_eq!(
+            Entry {
+                kind,
+                scope,
+                breaking,
+                description
+            },
+            Entry::parse(subject)
+        );
+    }
+
+    #[test]
+    fn render_section_basics() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).expect("must be valid");
+        assert_eq!(
+            "## 1.2.0 - 2024-03-09\n\n### Features\n\n- **BREAKING** **cli:** new flag\n- add thing\n\n### Bug Fixes\n\n- fix bug\n\n### Chores\n\n- tidy\n\n### Other\n\n- Update README\n",
+            render_section(
+                "1.2.0",
+                date,
+                &[
+                    "chore: tidy",
+                    "feat(cli)!: new flag",
+                    "Update README",
+                    "fix: fix bug",
+                    "feat: add thing",
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn render_section_no_commits() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).expect("must be valid");
+        assert_eq!(
+            "## 1.2.0 - 2024-03-09\n",
+            render_section::<&str>("1.2.0", date, &[])
+        );
+    }
+
+    #[rstest]
+    #[case("# Changelog\n\n## 2.0.0\n", "")]
+    #[case("# Changelog\n\n## 2.0.0\n", "\n")]
+    #[case(
+        "# Changelog\n\nIntro\n\n## 2.0.0\n\n## 1.0.0\n\n- old\n",
+        "# Changelog\n\nIntro\n\n## 1.0.0\n\n- old\n"
+    )]
+    #[case("# Changes\n\n## 2.0.0\n", "# Changes\n")]
+    #[case("# Changes\n\n## 2.0.0\n", "# Changes\n\n")]
+    #[case("# Changes\n\n## 2.0.0\n", "# Changes")]
+    fn prepend_section_basics(#[case] expected_result: &str, #[case] existing: &str) {
+        assert_eq!(expected_result, prepend_section(existing, "## 2.0.0\n"));
+    }
+}
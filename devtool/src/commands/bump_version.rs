@@ -20,169 +20,941 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 use crate::app::App;
+use crate::args::{LockStrategy, OnDrift, OnNoCommits, OnNoManifests};
+use crate::changelog::{render_section, update_changelog};
+use crate::commit_message_template::CommitMessageTemplate;
+use crate::constants::{CHANGELOG_FILE_NAME, GITHUB_TOKEN_ENV_NAME, VERSION_ENV_NAME};
+use crate::dirty_tree_policy::DirtyTreePolicy;
+use crate::error::{DevtoolError, DevtoolResult};
+use crate::github::GitHubRelease;
+use crate::manifests::{
+    set_version_keys, set_version_manifest, update_version_keys, update_version_manifest,
+    ManifestKind, ManifestUpdate, ManifestUpdater,
+};
 use crate::project_info::ProjectInfo;
-use anyhow::{bail, Result};
-use devtool_version::Version;
-use joatmon::{read_toml_file_edit, safe_write_file};
-use lazy_static::lazy_static;
+use crate::release_state::ReleaseState;
+use crate::reporter::Reporter;
+use crate::serialization::Config;
+use crate::tag_template::TagTemplate;
+use crate::text_file::read_toml_file_edit;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use devtool_git::{DescribeOptions, GitDescription, StatusEntry};
+use devtool_version::{Bump, ChangeKind, Version};
+use glob::Pattern;
+use log::{debug, trace};
 use path_absolutize::Absolutize;
-use std::io::Result as IOResult;
-use std::path::Path;
+use std::env::var;
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use toml_edit::value;
+use std::sync::LazyLock;
+use toml_edit::{DocumentMut, Item};
 
-lazy_static! {
-    static ref INITIAL_VERSION: Version = "v0.0.0".parse::<Version>().expect("init: must succeed");
+static INITIAL_VERSION: LazyLock<Version> =
+    LazyLock::new(|| "v0.0.0".parse::<Version>().expect("init: must succeed"));
+
+#[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct BumpVersionOptions {
+    pub version: Option<Version>,
+    pub bump: Option<Bump>,
+    pub zerover: Option<bool>,
+    pub push_all: Option<bool>,
+    pub tags_only: bool,
+    pub push_dry_run: bool,
+    pub on_no_commits: OnNoCommits,
+    pub on_drift: OnDrift,
+    pub on_no_manifests: OnNoManifests,
+    pub tag_template: Option<String>,
+    pub tag_match: Option<String>,
+    pub dirty_tree_policy: Option<DirtyTreePolicy>,
+    pub lock_strategy: Option<LockStrategy>,
+    pub tag_message_file: Option<PathBuf>,
+    pub tag_prefix: Option<bool>,
+    pub commit_message_template: Option<String>,
+    pub only_if_changed: bool,
+    pub sign: bool,
+    pub sign_commit: bool,
+    pub commit_date: Option<DateTime<FixedOffset>>,
+    pub github_release: bool,
+    pub exclude_patterns: Vec<String>,
+    pub allowed_branches: Vec<String>,
+    pub allow_dirty_config: bool,
+    pub list_files: bool,
+    pub dry_run: bool,
+    pub changelog: bool,
+    pub ignore_untracked: bool,
+    pub max_depth: Option<usize>,
+    pub fetch: bool,
+    pub no_verify: bool,
 }
 
-pub fn bump_version(app: &App, version: &Option<Version>, push_all: bool) -> Result<()> {
+#[allow(clippy::too_many_lines)]
+pub fn bump_version(app: &App, options: BumpVersionOptions) -> DevtoolResult<()> {
+    let BumpVersionOptions {
+        version,
+        bump,
+        zerover,
+        push_all,
+        tags_only,
+        push_dry_run,
+        on_no_commits,
+        on_drift,
+        on_no_manifests,
+        tag_template,
+        tag_match,
+        dirty_tree_policy,
+        lock_strategy,
+        tag_message_file,
+        tag_prefix,
+        commit_message_template,
+        only_if_changed,
+        sign,
+        sign_commit,
+        commit_date,
+        github_release,
+        exclude_patterns,
+        allowed_branches,
+        allow_dirty_config,
+        list_files,
+        dry_run,
+        changelog,
+        ignore_untracked,
+        max_depth,
+        fetch,
+        no_verify,
+    } = options;
+
+    if let Some(path) = &tag_message_file {
+        check_tag_message_file(path)?;
+    }
+
+    let config = app.read_config()?;
+    if list_files {
+        let project_info = ProjectInfo::read(app, config.as_ref(), max_depth)?;
+        for path in bump_files(&app.git.dir, &project_info, config.as_ref())? {
+            println!("{}", path.display());
+        }
+        return Ok(());
+    }
+
+    let push_all = resolve_push_all(push_all, config.as_ref());
+
     if app.git.read_config("user.name")?.is_none() {
-        bail!("Git user name is not set")
+        return Err(anyhow!("Git user name is not set").into());
     }
 
     if app.git.read_config("user.email")?.is_none() {
-        bail!("Git e-mail address is not set")
+        return Err(anyhow!("Git e-mail address is not set").into());
     }
 
-    let branch = app.git.get_current_branch()?;
-    if branch != "main" && branch != "master" {
-        bail!("Must be on the \"main\" or \"master\" branch")
+    if sign || sign_commit {
+        check_signing_key(app.git.read_config("user.signingkey")?.as_deref())?;
     }
 
-    if !app.git.status(false)?.is_empty() {
-        bail!("Git working directory is not clean: please revert or commit pending changes and try again")
+    let github_release = if github_release {
+        if push_dry_run || !push_all {
+            return Err(anyhow!("GitHub release requires commits and tags to be pushed").into());
+        }
+
+        Some(GitHubRelease::new(
+            app.git.get_remote_url("origin")?.as_deref(),
+            var(GITHUB_TOKEN_ENV_NAME).ok().as_deref(),
+        )?)
+    } else {
+        None
+    };
+
+    let branch = app.git.get_current_branch()?;
+    let upstream = app.git.get_upstream(&branch)?;
+    let upstream_parts = upstream.as_deref().and_then(|u| u.split_once('/'));
+    let default_branch = match upstream_parts {
+        Some((remote, _)) => app.git.default_branch(remote)?,
+        None => None,
+    };
+
+    let allowed_branches = resolve_allowed_branches(allowed_branches, config.as_ref());
+    if !is_release_branch(
+        &branch,
+        upstream_parts.map(|(_, b)| b),
+        default_branch.as_deref(),
+        &branch_patterns(&allowed_branches)?,
+    ) {
+        return Err(anyhow!(
+            "Must be on one of the allowed branches ({}) or a branch tracking the remote default branch",
+            allowed_branches.join(", ")
+        ).into());
     }
 
-    if app.git.get_upstream(&branch)?.is_none() {
-        bail!(
+    if upstream.is_none() {
+        return Err(DevtoolError::NoUpstream(anyhow!(
             "Branch {} has no upstream set: set with git push -u origin {} or similar",
             branch,
             branch
-        );
+        )));
     }
 
-    let project_info = app.read_config()?.map_or_else(
-        || ProjectInfo::infer(app),
-        |c| {
-            let cargo_toml_paths = c
-                .cargo_toml_paths
-                .into_iter()
-                .map(|p| p.absolutize_from(&app.git.dir).map(|p| p.to_path_buf()))
-                .collect::<IOResult<Vec<_>>>()?;
-            let pyproject_toml_paths = c
-                .pyproject_toml_paths
-                .into_iter()
-                .map(|p| p.absolutize_from(&app.git.dir).map(|p| p.to_path_buf()))
-                .collect::<IOResult<Vec<_>>>()?;
-            Ok(ProjectInfo {
-                cargo_toml_paths,
-                pyproject_toml_paths,
-            })
-        },
-    )?;
+    // The release is driven by config so changes to it should be committed first
+    if !allow_dirty_config {
+        if let Some(config_path) = app.find_config_path() {
+            if is_path_changed(&app.git.status_entries()?, &app.git.dir, &config_path) {
+                app.reporter.warn(&format!(
+                    "Config file {} has uncommitted changes",
+                    config_path.display()
+                ))?;
+            }
+        }
+    }
+
+    DirtyTreePolicy::resolve(dirty_tree_policy, config.as_ref(), DirtyTreePolicy::Error)
+        .check(app, !ignore_untracked)?;
+
+    let tag_template = tag_template
+        .or_else(|| config.as_ref().and_then(|c| c.tag_template.clone()))
+        .map(TagTemplate::new)
+        .transpose()?;
+    let commit_message_template = commit_message_template
+        .or_else(|| {
+            config
+                .as_ref()
+                .and_then(|c| c.commit_message_template.clone())
+        })
+        .map(CommitMessageTemplate::new)
+        .transpose()?
+        .unwrap_or_default();
+    let description = app.git.describe(&DescribeOptions {
+        match_patterns: match_patterns(
+            tag_match.or_else(|| config.as_ref().and_then(|c| c.tag_match.clone())),
+            tag_template.as_ref(),
+        ),
+        long: true,
+        ..Default::default()
+    })?;
+
+    let merged_tags = app.git.merged_tags()?;
+    let base_tag = version_base_tag(description.as_ref(), &merged_tags, tag_template.as_ref())
+        .map(String::from);
 
-    let new_version = if let Some(version) = version {
-        version.clone()
+    if only_if_changed {
+        if let Some(base_tag) = &base_tag {
+            if !app.git.has_changes_since(base_tag)? {
+                println!("No content changes since most recent tag \"{base_tag}\": skipping");
+                return Ok(());
+            }
+        }
+    }
+
+    let project_info = ProjectInfo::read(app, config.as_ref(), max_depth)?;
+    check_no_manifests(&project_info, on_no_manifests)?;
+    for path in &project_info.setup_py_paths {
+        app.reporter.warn(&format!(
+            "Version in {} is not updated automatically and must be edited manually",
+            path.display()
+        ))?;
+    }
+
+    if on_drift != OnDrift::Ignore {
+        if let Some(description) = &description {
+            let tag_version = match &tag_template {
+                Some(t) => t.parse_version(&description.tag).ok(),
+                None => description.tag.parse::<Version>().ok(),
+            };
+            if let Some(tag_version) = tag_version {
+                let mut manifest_versions = Vec::new();
+                for (path, kind) in project_info.manifests() {
+                    if let Some(version) = kind.read_version(&path)? {
+                        manifest_versions.push((path, version));
+                    }
+                }
+                check_drift(&app.reporter, on_drift, &tag_version, &manifest_versions)?;
+            }
+        }
+    }
+
+    let zerover = zerover
+        .or_else(|| config.as_ref().and_then(|c| c.zerover))
+        .unwrap_or(false);
+    let mut new_version = if let Some(version) =
+        resolve_version(version.as_ref(), var(VERSION_ENV_NAME).ok().as_deref())?
+    {
+        check_explicit_version(
+            &version,
+            highest_version(&merged_tags, tag_template.as_ref()).as_ref(),
+        )?;
+        version
     } else {
-        get_new_version(app, &INITIAL_VERSION)?
+        let manifest_version = if description.is_none() {
+            cargo_toml_version(&project_info)?
+        } else {
+            None
+        };
+        match next_version(
+            description,
+            &merged_tags,
+            &INITIAL_VERSION,
+            manifest_version,
+            bump,
+            zerover,
+            on_no_commits,
+            tag_template.as_ref(),
+        )? {
+            Some(version) => version,
+            None => return Ok(()),
+        }
     };
 
-    println!("project_info={project_info:#?}");
-    println!("new_version={new_version}");
-    println!("cargo_toml_paths={:#?}", project_info.cargo_toml_paths);
-    println!(
-        "pyproject_toml_paths={:#?}",
+    // Explicit preference overrides prefix inferred from most recent tag
+    if let Some(value) = tag_prefix.or_else(|| config.as_ref().and_then(|c| c.tag_prefix)) {
+        new_version.set_prefix(value);
+    }
+
+    trace!("project_info={project_info:#?}");
+    debug!("new_version={new_version}");
+    debug!("cargo_toml_paths={:?}", project_info.cargo_toml_paths);
+    debug!(
+        "pyproject_toml_paths={:?}",
         project_info.pyproject_toml_paths
     );
+    debug!("gradle_paths={:?}", project_info.gradle_paths);
+    debug!("package_json_paths={:?}", project_info.package_json_paths);
+    debug!("setup_cfg_paths={:?}", project_info.setup_cfg_paths);
 
     let mut new_version_without_prefix = new_version.dupe();
     new_version_without_prefix.set_prefix(false);
 
-    let mut file_change = false;
+    let tag = tag_template
+        .as_ref()
+        .map_or_else(|| new_version.to_string(), |t| t.format(&new_version));
 
-    if !project_info.cargo_toml_paths.is_empty() {
-        file_change = true;
+    // Avoid clobbering a tag pushed by someone else since the last fetch
+    if fetch {
+        app.git.fetch(false)?;
+        if let Some((remote, _)) = upstream_parts {
+            if app.git.remote_tag_exists(remote, &tag)? {
+                return Err(anyhow!("Tag {tag} already exists on remote {remote}").into());
+            }
+        }
+    }
 
-        for path in project_info.cargo_toml_paths {
-            update_cargo_toml(app, &path, &new_version_without_prefix)?;
+    let all_tags = app.git.list_tags()?;
+    for existing_tag in out_of_order_tags(
+        &sorted_tag_versions(&all_tags, tag_template.as_ref()),
+        &new_version,
+    ) {
+        app.reporter.warn(&format!(
+            "Existing tag {existing_tag} is not lower than new version {new_version}"
+        ))?;
+    }
+
+    let changelog_section = if changelog {
+        Some(render_section(
+            &new_version_without_prefix.to_string(),
+            changelog_date(commit_date),
+            &app.git
+                .log_range(base_tag.as_deref(), "HEAD")?
+                .iter()
+                .map(|c| c.subject.as_str())
+                .collect::<Vec<_>>(),
+        ))
+    } else {
+        None
+    };
+
+    if dry_run {
+        println!("DRY RUN: nothing will be written, committed, tagged or pushed");
+        println!("DRY RUN: new version would be {new_version}");
+        for path in changed_files(
+            &app.git.dir,
+            &project_info,
+            config.as_ref(),
+            &exclude_patterns,
+            &new_version_without_prefix,
+        )? {
+            println!("DRY RUN: would update {}", path.display());
+        }
+        if let Some(section) = &changelog_section {
+            println!("DRY RUN: would add section to {CHANGELOG_FILE_NAME}:\n{section}");
+        }
+        println!("DRY RUN: would create tag {tag}");
+        if push_all && !push_dry_run {
+            if tags_only {
+                println!("DRY RUN: would push tags");
+            } else {
+                println!("DRY RUN: would push commits and tags");
+            }
+        }
+        return Ok(());
+    }
+
+    let state = ReleaseState {
+        version: new_version_without_prefix.to_string(),
+        tag: tag.clone(),
+        commit_message: Some(
+            commit_message_template.format(&new_version_without_prefix.to_string()),
+        ),
+        tag_message_file: tag_message_file.clone(),
+        sign,
+        sign_commit,
+        commit_date: commit_date.map(|d| d.to_rfc3339()),
+        no_verify,
+        push_all: push_all && !push_dry_run,
+        tags_only,
+        ..Default::default()
+    };
+    state.record(&app.git.dir, |state| {
+        // Restore edited files if anything fails before the commit is created
+        let edit = |state: &mut ReleaseState| -> Result<()> {
+            let mut cargo_toml_change = false;
+            for (path, kind) in project_info.manifests() {
+                if kind.update_version(&path, &new_version_without_prefix)? {
+                    for version_path in kind.version_paths(&path)? {
+                        app.git.add(&version_path)?;
+                        state.edited_files.push(version_path);
+                    }
+
+                    cargo_toml_change |= kind == ManifestKind::Cargo;
+                }
+            }
+
+            if let Some(c) = config.as_ref().filter(|c| !c.version_keys.is_empty()) {
+                let excludes = c
+                    .version_key_excludes
+                    .iter()
+                    .chain(&exclude_patterns)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for (path, kind) in project_info.manifests() {
+                    if !kind.is_toml()
+                        || !update_version_keys(
+                            &path,
+                            &c.version_keys,
+                            &excludes,
+                            &new_version_without_prefix,
+                        )?
+                    {
+                        continue;
+                    }
+
+                    cargo_toml_change |= kind == ManifestKind::Cargo;
+                    if !state.edited_files.contains(&path) {
+                        app.git.add(&path)?;
+                        state.edited_files.push(path);
+                    }
+                }
+            }
+
+            if let Some(version_manifest) =
+                config.as_ref().and_then(|c| c.version_manifest.as_ref())
+            {
+                let mut package_names = Vec::new();
+                for (path, kind) in project_info.manifests() {
+                    if let Some(package_name) = kind.package_name(&path)? {
+                        package_names.push(package_name);
+                    }
+                }
+
+                let path = version_manifest
+                    .path
+                    .absolutize_from(&app.git.dir)?
+                    .to_path_buf();
+                if update_version_manifest(
+                    &path,
+                    &version_manifest.table,
+                    &package_names,
+                    &new_version_without_prefix,
+                )? {
+                    app.git.add(&path)?;
+                    state.edited_files.push(path);
+                }
+            }
+
+            if let Some(section) = &changelog_section {
+                let path = app.git.dir.join(CHANGELOG_FILE_NAME);
+                update_changelog(&path, section)?;
+                app.git.add(&path)?;
+                state.edited_files.push(path);
+            }
+
+            if cargo_toml_change {
+                regenerate_cargo_lock(app, lock_strategy, &mut state.edited_files)?;
+            }
+
+            if !state.edited_files.is_empty() {
+                state.commit(app)?;
+            }
+            Ok(())
+        };
+        if let Err(e) = edit(state) {
+            return Err(state.rollback_edits(app, e));
         }
 
-        regenerate_cargo_lock(app)?;
+        state.create_tag(app)?;
+
+        if push_dry_run {
+            let output = app.git.push_dry_run()?;
+            if !output.is_empty() {
+                println!("{output}");
+            }
+            println!(
+                "Push dry run succeeded: local commit and tag {tag} were created but not pushed"
+            );
+        } else if state.push_all {
+            state.push(app)?;
+        } else {
+            println!("Skipping push of commits and tags");
+        }
+
+        Ok(())
+    })?;
+
+    if let Some(github_release) = github_release {
+        let notes = release_notes(changelog_section.as_deref(), tag_message_file.as_deref())?;
+        github_release.create(&tag, notes.as_deref())?;
+        println!("Created GitHub release {tag}");
+    }
+
+    Ok(())
+}
+
+// The changelog section describes exactly this release and GitHub generates
+// notes when neither it nor a tag message file is available
+fn release_notes(
+    changelog_section: Option<&str>,
+    tag_message_file: Option<&Path>,
+) -> Result<Option<String>> {
+    if let Some(section) = changelog_section {
+        return Ok(Some(String::from(section)));
     }
+    Ok(tag_message_file.map(read_to_string).transpose()?)
+}
 
-    if !project_info.pyproject_toml_paths.is_empty() {
-        file_change = true;
+fn is_path_changed(status_entries: &[StatusEntry], dir: &Path, path: &Path) -> bool {
+    let Ok(relative_path) = path.strip_prefix(dir) else {
+        return false;
+    };
+    status_entries
+        .iter()
+        .any(|e| Path::new(&e.path) == relative_path)
+}
 
-        for path in project_info.pyproject_toml_paths {
-            update_pyproject_toml(app, &path, &new_version_without_prefix)?;
+// Command-line flags take precedence over config and pushing is on by default
+fn resolve_push_all(push_all: Option<bool>, config: Option<&Config>) -> bool {
+    push_all
+        .or_else(|| config.and_then(|c| c.push))
+        .unwrap_or(true)
+}
+
+// Repository-relative paths of the files a bump to the given version would
+// actually rewrite, mirroring the updates made by bump_version
+fn changed_files(
+    dir: &Path,
+    project_info: &ProjectInfo,
+    config: Option<&Config>,
+    exclude_patterns: &[String],
+    version: &Version,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut cargo_toml_change = false;
+    for (path, kind) in project_info.manifests() {
+        for (path, update) in kind.plan_version(&path, version)? {
+            if matches!(update, ManifestUpdate::Changed(_)) {
+                cargo_toml_change |= kind == ManifestKind::Cargo;
+                paths.push(path);
+            }
         }
     }
 
-    if file_change {
-        app.git
-            .commit(format!("Bump version to {new_version_without_prefix}"))?;
-        println!("Bumped Cargo and Python package version to {new_version_without_prefix}");
+    if let Some(c) = config.filter(|c| !c.version_keys.is_empty()) {
+        let excludes = c
+            .version_key_excludes
+            .iter()
+            .chain(exclude_patterns)
+            .cloned()
+            .collect::<Vec<_>>();
+        for (path, kind) in project_info.manifests() {
+            if kind.is_toml()
+                && matches!(
+                    set_version_keys(&read_to_string(&path)?, &c.version_keys, &excludes, version)?,
+                    ManifestUpdate::Changed(_)
+                )
+            {
+                cargo_toml_change |= kind == ManifestKind::Cargo;
+                paths.push(path);
+            }
+        }
     }
 
-    let tag = new_version.to_string();
-    app.git.create_annotated_tag(&tag)?;
-    println!("Created tag {tag}");
+    if let Some(version_manifest) = config.and_then(|c| c.version_manifest.as_ref()) {
+        let mut package_names = Vec::new();
+        for (path, kind) in project_info.manifests() {
+            if let Some(package_name) = kind.package_name(&path)? {
+                package_names.push(package_name);
+            }
+        }
 
-    if push_all {
-        app.git.push_all()?;
-        println!("Pushed commits and tags");
-    } else {
-        println!("Skipping push of commits and tags");
+        let path = version_manifest.path.absolutize_from(dir)?.to_path_buf();
+        let original = if path.is_file() {
+            read_to_string(&path)?
+        } else {
+            String::new()
+        };
+        if matches!(
+            set_version_manifest(&original, &version_manifest.table, &package_names, version)?,
+            ManifestUpdate::Changed(_)
+        ) {
+            paths.push(path);
+        }
     }
 
-    Ok(())
+    if cargo_toml_change {
+        let cargo_lock_path = dir.join("Cargo.lock");
+        if cargo_lock_path.is_file() {
+            paths.push(cargo_lock_path);
+        }
+    }
+
+    Ok(relative_paths(dir, paths))
+}
+
+// Repository-relative paths of all files a version bump may modify
+fn bump_files(
+    dir: &Path,
+    project_info: &ProjectInfo,
+    config: Option<&Config>,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for (path, kind) in project_info.manifests() {
+        paths.extend(kind.version_paths(&path)?);
+    }
+
+    if !project_info.cargo_toml_paths.is_empty() {
+        let cargo_lock_path = dir.join("Cargo.lock");
+        if cargo_lock_path.is_file() {
+            paths.push(cargo_lock_path);
+        }
+    }
+
+    if let Some(version_manifest) = config.and_then(|c| c.version_manifest.as_ref()) {
+        paths.push(version_manifest.path.absolutize_from(dir)?.to_path_buf());
+    }
+
+    Ok(relative_paths(dir, paths))
+}
+
+// Makes paths relative to the repository and drops duplicates
+fn relative_paths(dir: &Path, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut result = Vec::new();
+    for path in paths {
+        let path = path
+            .strip_prefix(dir)
+            .map_or_else(|_| path.clone(), Path::to_path_buf);
+        if !result.contains(&path) {
+            result.push(path);
+        }
+    }
+    result
+}
+
+fn check_no_manifests(project_info: &ProjectInfo, on_no_manifests: OnNoManifests) -> Result<()> {
+    if !project_info.manifests().is_empty() {
+        return Ok(());
+    }
+
+    let searched = project_info
+        .ecosystems()
+        .iter()
+        .map(|e| format!("{} ({})", e.name, e.file_name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match on_no_manifests {
+        OnNoManifests::TagOnly => {
+            println!("No manifests found (searched for {searched}): creating tag only");
+            Ok(())
+        }
+        OnNoManifests::Error => bail!("No manifests found (searched for {searched})"),
+    }
+}
+
+fn check_signing_key(signing_key: Option<&str>) -> Result<()> {
+    match signing_key {
+        Some(k) if !k.trim().is_empty() => Ok(()),
+        _ => bail!("Signing was requested but Git signing key is not set: set with git config user.signingkey <key> (and git config gpg.format ssh for SSH keys)"),
+    }
 }
 
-fn get_new_version(app: &App, default: &Version) -> Result<Version> {
-    Ok(match app.git.describe()? {
-        Some(description) => {
-            if description.offset.is_none() {
-                bail!("No commits since most recent tag \"{}\"", description.tag)
+fn check_drift(
+    reporter: &Reporter,
+    on_drift: OnDrift,
+    tag_version: &Version,
+    manifest_versions: &[(PathBuf, Version)],
+) -> Result<()> {
+    let messages = manifest_versions
+        .iter()
+        .filter(|(_, v)| tag_version.change_kind(v) != ChangeKind::None)
+        .map(|(p, v)| {
+            format!(
+                "{} has version {v} but most recent tag has version {tag_version}",
+                p.display()
+            )
+        })
+        .collect::<Vec<_>>();
+    if messages.is_empty() {
+        return Ok(());
+    }
+
+    match on_drift {
+        OnDrift::Error => bail!(
+            "Manifest versions do not match most recent tag:\n{}",
+            messages.join("\n")
+        ),
+        OnDrift::Warn => {
+            for message in messages {
+                reporter.warn(&message)?;
             }
+            Ok(())
+        }
+        OnDrift::Ignore => Ok(()),
+    }
+}
 
-            let mut version = description.tag.parse::<Version>()?;
-            println!("description={description:#?}");
-            version.increment();
-            version
+fn check_tag_message_file(path: &Path) -> Result<()> {
+    let message = match read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            bail!("Tag message file {} does not exist", path.display())
         }
-        None => default.clone(),
-    })
+        Err(e) => bail!(e),
+    };
+
+    if message.trim().is_empty() {
+        bail!("Tag message file {} is empty", path.display())
+    }
+
+    Ok(())
 }
 
-fn update_cargo_toml(app: &App, path: &Path, new_version_without_prefix: &Version) -> Result<()> {
-    let mut doc = read_toml_file_edit(path)?;
+fn resolve_allowed_branches(allowed_branches: Vec<String>, config: Option<&Config>) -> Vec<String> {
+    if !allowed_branches.is_empty() {
+        return allowed_branches;
+    }
 
-    if let Some(package) = doc
-        .as_table_mut()
-        .get_mut("package")
-        .and_then(toml_edit::Item::as_table_mut)
-    {
-        _ = package.insert("version", value(format!("{new_version_without_prefix}")));
-        let result = doc.to_string();
-        safe_write_file(path, result, true)?;
-        app.git.add(path)?;
+    match config {
+        Some(c) if !c.allowed_branches.is_empty() => c.allowed_branches.clone(),
+        _ => vec![String::from("main"), String::from("master")],
+    }
+}
+
+fn branch_patterns(allowed_branches: &[String]) -> Result<Vec<Pattern>> {
+    allowed_branches
+        .iter()
+        .map(|b| {
+            Pattern::new(b).map_err(|e| anyhow!("Invalid allowed branch pattern \"{b}\": {e}"))
+        })
+        .collect()
+}
+
+fn is_release_branch(
+    branch: &str,
+    upstream_branch: Option<&str>,
+    default_branch: Option<&str>,
+    allowed_branches: &[Pattern],
+) -> bool {
+    let is_allowed = |name: &str| allowed_branches.iter().any(|p| p.matches(name));
+    let is_default = |name: &str| default_branch.map_or_else(|| is_allowed(name), |d| name == d);
+    is_allowed(branch) || upstream_branch.is_some_and(is_default)
+}
+
+fn resolve_version(
+    version: Option<&Version>,
+    env_version: Option<&str>,
+) -> Result<Option<Version>> {
+    if let Some(version) = version {
+        return Ok(Some(version.clone()));
     }
 
+    match env_version {
+        Some(s) if !s.is_empty() => match s.parse::<Version>() {
+            Ok(version) => Ok(Some(version)),
+            Err(e) => {
+                bail!("Invalid version \"{s}\" in environment variable {VERSION_ENV_NAME}: {e}")
+            }
+        },
+        _ => Ok(None),
+    }
+}
+
+// Guards against an explicit version that would go backwards or repeat the current tag
+fn check_explicit_version(version: &Version, current: Option<&Version>) -> Result<()> {
+    if let Some(current) = current {
+        if version <= current {
+            bail!("Version {version} is not greater than current version {current}")
+        }
+    }
     Ok(())
 }
 
-fn regenerate_cargo_lock(app: &App) -> Result<()> {
+// Restricts tags considered by git describe to those that look like versions
+pub(super) fn match_patterns(
+    tag_match: Option<String>,
+    tag_template: Option<&TagTemplate>,
+) -> Vec<String> {
+    if let Some(tag_match) = tag_match {
+        return vec![tag_match];
+    }
+
+    tag_template.map_or_else(
+        || vec![String::from("v[0-9]*"), String::from("[0-9]*")],
+        |t| vec![t.glob()],
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn next_version(
+    description: Option<GitDescription>,
+    merged_tags: &[String],
+    default: &Version,
+    manifest_version: Option<Version>,
+    bump: Option<Bump>,
+    zerover: bool,
+    on_no_commits: OnNoCommits,
+    tag_template: Option<&TagTemplate>,
+) -> Result<Option<Version>> {
+    if let Some(description) = description {
+        if description.is_exact() {
+            match on_no_commits {
+                OnNoCommits::Error => {
+                    bail!("No commits since most recent tag \"{}\"", description.tag)
+                }
+                OnNoCommits::Skip => {
+                    println!(
+                        "No commits since most recent tag \"{}\": skipping",
+                        description.tag
+                    );
+                    return Ok(None);
+                }
+                OnNoCommits::Force => {}
+            }
+        }
+        debug!("description={description:?}");
+    }
+
+    // Without any tags, a manifest version above the default is the
+    // starting point so that the first release continues from it
+    let Some(version) = highest_version(merged_tags, tag_template).or_else(|| {
+        manifest_version.filter(|v| v > default).map(|mut v| {
+            v.set_prefix(default.to_string().starts_with('v'));
+            v
+        })
+    }) else {
+        return Ok(Some(default.clone()));
+    };
+
+    Ok(Some(match bump {
+        Some(bump) => version.bump(bump, zerover)?,
+        None => version.next()?,
+    }))
+}
+
+fn cargo_toml_version(project_info: &ProjectInfo) -> Result<Option<Version>> {
+    match project_info.cargo_toml_paths.first() {
+        Some(path) => ManifestKind::Cargo.read_version(path),
+        None => Ok(None),
+    }
+}
+
+fn highest_version(tags: &[String], tag_template: Option<&TagTemplate>) -> Option<Version> {
+    sorted_tag_versions(tags, tag_template)
+        .pop()
+        .map(|(version, _)| version)
+}
+
+// The section is dated like the release commit
+fn changelog_date(commit_date: Option<DateTime<FixedOffset>>) -> NaiveDate {
+    commit_date.map_or_else(|| Local::now().date_naive(), |d| d.date_naive())
+}
+
+// The bump starts from the highest merged version, which is not necessarily the
+// tag that describe finds nearest to HEAD
+fn version_base_tag<'a>(
+    description: Option<&'a GitDescription>,
+    merged_tags: &'a [String],
+    tag_template: Option<&TagTemplate>,
+) -> Option<&'a str> {
+    sorted_tag_versions(merged_tags, tag_template)
+        .pop()
+        .map(|(_, tag)| tag)
+        .or_else(|| description.map(|d| d.tag.as_str()))
+}
+
+// Versions of tags in ascending order, skipping those that do not parse
+pub(super) fn sorted_tag_versions<'a>(
+    tags: &'a [String],
+    tag_template: Option<&TagTemplate>,
+) -> Vec<(Version, &'a str)> {
+    let mut versions = tags
+        .iter()
+        .filter_map(|tag| {
+            match tag_template {
+                Some(t) => t.parse_version(tag).ok(),
+                None => tag.parse::<Version>().ok(),
+            }
+            .map(|version| (version, tag.as_str()))
+        })
+        .collect::<Vec<_>>();
+    versions.sort_by(|(a, _), (b, _)| a.cmp_exact(b));
+    versions
+}
+
+// Tags, possibly on other branches, whose versions are not lower than the new version
+fn out_of_order_tags<'a>(versions: &[(Version, &'a str)], new_version: &Version) -> Vec<&'a str> {
+    versions
+        .iter()
+        .filter(|(version, _)| version >= new_version)
+        .map(|(_, tag)| *tag)
+        .collect()
+}
+
+fn regenerate_cargo_lock(
+    app: &App,
+    lock_strategy: Option<LockStrategy>,
+    edited_files: &mut Vec<PathBuf>,
+) -> Result<()> {
     let cargo_toml_path = app.git.dir.join("Cargo.toml");
     let cargo_lock_path = app.git.dir.join("Cargo.lock");
     if app.git.is_tracked(&cargo_toml_path)? && app.git.is_tracked(&cargo_lock_path)? {
-        if !Command::new("cargo")
-            .arg("build")
-            .arg("--manifest-path")
-            .arg(&cargo_toml_path)
-            .status()?
-            .success()
-        {
-            bail!("cargo build failed")
+        let doc = read_toml_file_edit(&cargo_toml_path)?;
+        let lock_strategy = lock_strategy.unwrap_or_else(|| {
+            default_lock_strategy(&doc, app.git.dir.join("src").join("main.rs").is_file())
+        });
+
+        let mut command = Command::new("cargo");
+        let subcommand = match lock_strategy {
+            LockStrategy::Build => {
+                command.arg("build");
+                "build"
+            }
+            LockStrategy::Update => {
+                let Some(package_name) = doc
+                    .get("package")
+                    .and_then(|i| i.get("name"))
+                    .and_then(Item::as_str)
+                else {
+                    bail!("Cannot determine package name for cargo update")
+                };
+
+                command.arg("update").arg("--package").arg(package_name);
+                "update"
+            }
+            LockStrategy::Skip => return Ok(()),
+        };
+        command.arg("--manifest-path").arg(&cargo_toml_path);
+
+        // Cargo may rewrite the lock file before failing so it must be restored on rollback
+        edited_files.push(cargo_lock_path.clone());
+        if !command.status()?.success() {
+            bail!("cargo {subcommand} failed")
         }
 
         app.git.add(&cargo_lock_path)?;
@@ -191,23 +963,735 @@ fn regenerate_cargo_lock(app: &App) -> Result<()> {
     Ok(())
 }
 
-fn update_pyproject_toml(
-    app: &App,
-    path: &Path,
-    new_version_without_prefix: &Version,
-) -> Result<()> {
-    let mut doc = read_toml_file_edit(path)?;
+fn default_lock_strategy(doc: &DocumentMut, has_main_rs: bool) -> LockStrategy {
+    if doc.get("package").is_none() || doc.get("bin").is_some() {
+        return LockStrategy::Build;
+    }
 
-    if let Some(package) = doc
-        .as_table_mut()
-        .get_mut("project")
-        .and_then(toml_edit::Item::as_table_mut)
-    {
-        _ = package.insert("version", value(format!("{new_version_without_prefix}")));
-        let result = doc.to_string();
-        safe_write_file(path, result, true)?;
-        app.git.add(path)?;
+    if doc.get("lib").is_some() || !has_main_rs {
+        LockStrategy::Update
+    } else {
+        LockStrategy::Build
     }
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::{
+        branch_patterns, bump_files, changed_files, changelog_date, check_drift,
+        check_explicit_version, check_no_manifests, check_signing_key, default_lock_strategy,
+        highest_version, is_path_changed, is_release_branch, match_patterns, next_version,
+        out_of_order_tags, regenerate_cargo_lock, release_notes, resolve_allowed_branches,
+        resolve_push_all, resolve_version, sorted_tag_versions, version_base_tag,
+    };
+    use crate::app::App;
+    use crate::args::{LockStrategy, OnDrift, OnNoCommits, OnNoManifests};
+    use crate::project_info::ProjectInfo;
+    use crate::reporter::Reporter;
+    use crate::serialization::Config;
+    use crate::tag_template::TagTemplate;
+    use crate::test_dir::TestDir;
+    use anyhow::Result;
+    use chrono::DateTime;
+    use devtool_git::{GitDescription, StatusEntry};
+    use devtool_version::{Bump, Version};
+    use rstest::rstest;
+    use std::fs::{create_dir_all, write};
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use toml_edit::DocumentMut;
+
+    #[rstest]
+    #[case(true, None, None)]
+    #[case(true, None, Some(true))]
+    #[case(false, None, Some(false))]
+    #[case(true, Some(true), Some(false))]
+    #[case(false, Some(false), Some(true))]
+    #[case(false, Some(false), None)]
+    fn resolve_push_all_basics(
+        #[case] expected_result: bool,
+        #[case] push_all: Option<bool>,
+        #[case] config_push: Option<bool>,
+    ) {
+        let config = Config {
+            push: config_push,
+            ..Default::default()
+        };
+        assert_eq!(expected_result, resolve_push_all(push_all, Some(&config)));
+    }
+
+    #[test]
+    fn resolve_push_all_no_config() {
+        assert!(resolve_push_all(None, None));
+    }
+
+    #[rstest]
+    #[case(true, &[" M .devtool.yaml"], "/project/.devtool.yaml")]
+    #[case(true, &["?? Cargo.lock", " M sub/.devtool.yaml"], "/project/sub/.devtool.yaml")]
+    #[case(true, &["?? .devtool.yaml"], "/project/.devtool.yaml")]
+    #[case(false, &[" M Cargo.toml"], "/project/.devtool.yaml")]
+    #[case(false, &[" M sub/.devtool.yaml"], "/project/.devtool.yaml")]
+    #[case(false, &[], "/project/.devtool.yaml")]
+    #[case(false, &[" M .devtool.yaml"], "/home/user/.config/devtool/config.yaml")]
+    fn is_path_changed_basics(
+        #[case] expected_result: bool,
+        #[case] status: &[&str],
+        #[case] path: &str,
+    ) {
+        let status_entries = status
+            .iter()
+            .filter_map(StatusEntry::parse)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expected_result,
+            is_path_changed(&status_entries, Path::new("/project"), Path::new(path))
+        );
+    }
+
+    #[rstest]
+    #[case(true, OnDrift::Error, "1.2.3", false)]
+    #[case(true, OnDrift::Error, "v1.2.3", false)]
+    #[case(false, OnDrift::Error, "1.3.0", false)]
+    #[case(false, OnDrift::Error, "1.2.2", false)]
+    #[case(true, OnDrift::Warn, "1.3.0", false)]
+    #[case(false, OnDrift::Warn, "1.3.0", true)]
+    #[case(true, OnDrift::Warn, "1.2.3", true)]
+    #[case(true, OnDrift::Ignore, "1.3.0", false)]
+    #[case(true, OnDrift::Ignore, "1.3.0", true)]
+    fn check_drift_basics(
+        #[case] expected_ok: bool,
+        #[case] on_drift: OnDrift,
+        #[case] manifest_version: &str,
+        #[case] strict: bool,
+    ) -> Result<()> {
+        let manifest_versions = vec![
+            (
+                PathBuf::from("/project/Cargo.toml"),
+                "1.2.3".parse::<Version>()?,
+            ),
+            (
+                PathBuf::from("/project/pyproject.toml"),
+                manifest_version.parse::<Version>()?,
+            ),
+        ];
+        assert_eq!(
+            expected_ok,
+            check_drift(
+                &Reporter { strict },
+                on_drift,
+                &"v1.2.3".parse::<Version>()?,
+                &manifest_versions
+            )
+            .is_ok()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bump_files_fixture_repo() -> Result<()> {
+        let test_dir = TestDir::new("bump-files")?;
+        let dir = test_dir.path();
+        create_dir_all(dir.join("python"))?;
+        create_dir_all(dir.join("ignored"))?;
+        write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        )?;
+        write(dir.join("Cargo.lock"), "version = 3\n")?;
+        write(
+            dir.join("python").join("pyproject.toml"),
+            "[project]\nname = \"bar\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic]\nversion = { file = \"VERSION\" }\n",
+        )?;
+        write(
+            dir.join("ignored").join("Cargo.toml"),
+            "[package]\nname = \"baz\"\nversion = \"0.1.0\"\n",
+        )?;
+        write(dir.join(".devtoolignore"), "/ignored/\n")?;
+
+        let app = App::new(dir);
+        let config = serde_yaml::from_str::<Config>(
+            "version_manifest:\n  path: versions.toml\n  table: versions\n",
+        )?;
+        let result = ProjectInfo::read(&app, None, None)
+            .and_then(|project_info| bump_files(dir, &project_info, Some(&config)));
+
+        assert_eq!(
+            vec![
+                PathBuf::from("Cargo.toml"),
+                Path::new("python").join("VERSION"),
+                PathBuf::from("Cargo.lock"),
+                PathBuf::from("versions.toml"),
+            ],
+            result?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn changed_files_fixture_repo() -> Result<()> {
+        let test_dir = TestDir::new("changed-files")?;
+        let dir = test_dir.path();
+        create_dir_all(dir.join("python"))?;
+        write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        )?;
+        write(dir.join("Cargo.lock"), "version = 3\n")?;
+        write(
+            dir.join("python").join("pyproject.toml"),
+            "[project]\nname = \"bar\"\nversion = \"0.2.0\"\n",
+        )?;
+
+        let app = App::new(dir);
+        let config = serde_yaml::from_str::<Config>(
+            "version_manifest:\n  path: versions.toml\n  table: versions\n",
+        )?;
+        let version = "0.2.0".parse::<Version>()?;
+        let result = ProjectInfo::read(&app, None, None).and_then(|project_info| {
+            changed_files(dir, &project_info, Some(&config), &[], &version)
+        });
+
+        assert_eq!(
+            vec![
+                PathBuf::from("Cargo.toml"),
+                PathBuf::from("versions.toml"),
+                PathBuf::from("Cargo.lock"),
+            ],
+            result?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(true, OnNoManifests::TagOnly)]
+    #[case(false, OnNoManifests::Error)]
+    fn check_no_manifests_empty_repo(
+        #[case] expected_ok: bool,
+        #[case] on_no_manifests: OnNoManifests,
+    ) -> Result<()> {
+        let test_dir = TestDir::new(&format!("no-manifests-{on_no_manifests:?}"))?;
+        let dir = test_dir.path();
+        write(dir.join("README.md"), "# Project\n")?;
+        let project_info = ProjectInfo::infer(&App::new(dir), None);
+
+        assert_eq!(
+            expected_ok,
+            check_no_manifests(&project_info?, on_no_manifests).is_ok()
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(OnNoManifests::TagOnly)]
+    #[case(OnNoManifests::Error)]
+    fn check_no_manifests_with_manifests(#[case] on_no_manifests: OnNoManifests) -> Result<()> {
+        let project_info = ProjectInfo {
+            cargo_toml_paths: vec![PathBuf::from("/project/Cargo.toml")],
+            pyproject_toml_paths: Vec::new(),
+            gradle_paths: Vec::new(),
+            package_json_paths: Vec::new(),
+            setup_cfg_paths: Vec::new(),
+            setup_py_paths: Vec::new(),
+        };
+        check_no_manifests(&project_info, on_no_manifests)
+    }
+
+    #[rstest]
+    #[case(true, Some("ABCDEF0123456789"))]
+    #[case(false, Some(" "))]
+    #[case(false, None)]
+    fn check_signing_key_basics(#[case] expected_ok: bool, #[case] signing_key: Option<&str>) {
+        assert_eq!(expected_ok, check_signing_key(signing_key).is_ok());
+    }
+
+    #[rstest]
+    #[case(LockStrategy::Update, "[package]\nname = \"foo\"\n", false)]
+    #[case(LockStrategy::Build, "[package]\nname = \"foo\"\n", true)]
+    #[case(LockStrategy::Update, "[package]\nname = \"foo\"\n\n[lib]\n", true)]
+    #[case(
+        LockStrategy::Build,
+        "[package]\nname = \"foo\"\n\n[[bin]]\nname = \"foo\"\n",
+        false
+    )]
+    #[case(
+        LockStrategy::Build,
+        "[package]\nname = \"foo\"\n\n[lib]\n\n[[bin]]\nname = \"foo\"\n",
+        false
+    )]
+    #[case(LockStrategy::Build, "[workspace]\nmembers = [\"foo\"]\n", false)]
+    fn default_lock_strategy_basics(
+        #[case] expected_result: LockStrategy,
+        #[case] input: &str,
+        #[case] has_main_rs: bool,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result,
+            default_lock_strategy(&input.parse::<DocumentMut>()?, has_main_rs)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn regenerate_cargo_lock_failure_records_lock_file() -> Result<()> {
+        let test_dir = TestDir::new("regenerate-cargo-lock")?;
+        let dir = test_dir.path();
+        // Without any source files cargo build fails
+        write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        )?;
+        write(dir.join("Cargo.lock"), "version = 3\n")?;
+        for args in [
+            &["init", "--quiet"][..],
+            &["add", "Cargo.toml", "Cargo.lock"],
+        ] {
+            assert!(Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(args)
+                .status()?
+                .success());
+        }
+
+        let app = App::new(dir);
+        let mut edited_files = Vec::new();
+        assert!(regenerate_cargo_lock(&app, Some(LockStrategy::Build), &mut edited_files).is_err());
+        assert_eq!(vec![dir.join("Cargo.lock")], edited_files);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(true, "main", Some("main"), Some("main"))]
+    #[case(true, "master", None, None)]
+    #[case(true, "my-main", Some("main"), Some("main"))]
+    #[case(true, "my-main", Some("main"), None)]
+    #[case(true, "my-trunk", Some("trunk"), Some("trunk"))]
+    #[case(false, "my-trunk", Some("trunk"), None)]
+    #[case(false, "feature", Some("feature"), Some("main"))]
+    #[case(false, "feature", None, Some("main"))]
+    fn is_release_branch_basics(
+        #[case] expected_result: bool,
+        #[case] branch: &str,
+        #[case] upstream_branch: Option<&str>,
+        #[case] default_branch: Option<&str>,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result,
+            is_release_branch(
+                branch,
+                upstream_branch,
+                default_branch,
+                &branch_patterns(&resolve_allowed_branches(Vec::new(), None))?
+            )
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(true, "release/1.2", &["release/*"])]
+    #[case(true, "main", &["main", "release/*"])]
+    #[case(false, "main", &["release/*"])]
+    #[case(false, "feature/release/1.2", &["release/*"])]
+    fn is_release_branch_allowed(
+        #[case] expected_result: bool,
+        #[case] branch: &str,
+        #[case] allowed_branches: &[&str],
+    ) -> Result<()> {
+        let allowed_branches = allowed_branches
+            .iter()
+            .map(|b| String::from(*b))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expected_result,
+            is_release_branch(branch, None, None, &branch_patterns(&allowed_branches)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(&["release/*"], &["release/*"], &["trunk"])]
+    #[case(&["trunk"], &[], &["trunk"])]
+    #[case(&["main", "master"], &[], &[])]
+    fn resolve_allowed_branches_basics(
+        #[case] expected_result: &[&str],
+        #[case] allowed_branches: &[&str],
+        #[case] config_allowed_branches: &[&str],
+    ) {
+        let config = Config {
+            allowed_branches: config_allowed_branches
+                .iter()
+                .map(|b| String::from(*b))
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(
+            expected_result,
+            resolve_allowed_branches(
+                allowed_branches.iter().map(|b| String::from(*b)).collect(),
+                Some(&config)
+            )
+        );
+    }
+
+    #[test]
+    fn branch_patterns_invalid() {
+        assert!(branch_patterns(&[String::from("release/[")]).is_err());
+    }
+
+    #[rstest]
+    #[case(Some("v1.2.3"), Some("v1.2.3"), Some("v2.0.0"))]
+    #[case(Some("v1.2.3"), Some("v1.2.3"), None)]
+    #[case(Some("v2.0.0"), None, Some("v2.0.0"))]
+    #[case(None, None, Some(""))]
+    #[case(None, None, None)]
+    fn resolve_version_precedence(
+        #[case] expected_result: Option<&str>,
+        #[case] version: Option<&str>,
+        #[case] env_version: Option<&str>,
+    ) -> Result<()> {
+        let version = version.map(str::parse::<Version>).transpose()?;
+        assert_eq!(
+            expected_result.map(String::from),
+            resolve_version(version.as_ref(), env_version)?.map(|v| v.to_string())
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(true, "v1.2.4", Some("v1.2.3"))]
+    #[case(true, "v1.10.0", Some("v1.9.0"))]
+    #[case(true, "v0.1.0", None)]
+    #[case(false, "v1.2.3", Some("v1.2.3"))]
+    #[case(false, "1.2.3", Some("v1.2.3"))]
+    #[case(false, "v1.2.0", Some("v1.2"))]
+    #[case(false, "v1.9.0", Some("v1.10.0"))]
+    fn check_explicit_version_basics(
+        #[case] expected_result: bool,
+        #[case] version: &str,
+        #[case] current: Option<&str>,
+    ) -> Result<()> {
+        let version = version.parse::<Version>()?;
+        let current = current.map(str::parse::<Version>).transpose()?;
+        assert_eq!(
+            expected_result,
+            check_explicit_version(&version, current.as_ref()).is_ok()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_version_invalid_env() {
+        assert!(resolve_version(None, Some("garbage")).is_err());
+    }
+
+    #[rstest]
+    #[case(Err(()), "v1.2.3", OnNoCommits::Error)]
+    #[case(Ok(None), "v1.2.3", OnNoCommits::Skip)]
+    #[case(Ok(Some("v1.2.4")), "v1.2.3", OnNoCommits::Force)]
+    #[case(Err(()), "v1.2.3-0-gabcdef0", OnNoCommits::Error)]
+    #[case(Ok(None), "v1.2.3-0-gabcdef0", OnNoCommits::Skip)]
+    #[case(Ok(Some("v1.2.4")), "v1.2.3-0-gabcdef0", OnNoCommits::Force)]
+    #[case(Ok(Some("v1.2.4")), "v1.2.3-2-gabcdef0", OnNoCommits::Error)]
+    #[case(Ok(Some("v1.2.4")), "v1.2.3-2-gabcdef0", OnNoCommits::Skip)]
+    #[case(Ok(Some("v1.2.4")), "v1.2.3-2-gabcdef0", OnNoCommits::Force)]
+    fn next_version_on_no_commits(
+        #[case] expected_result: Result<Option<&str>, ()>,
+        #[case] description: &str,
+        #[case] on_no_commits: OnNoCommits,
+    ) -> Result<()> {
+        let default = "v0.0.0".parse::<Version>()?;
+        let description = GitDescription::parse(description);
+        let merged_tags = description
+            .iter()
+            .map(|d| d.tag.clone())
+            .collect::<Vec<_>>();
+        let result = next_version(
+            description,
+            &merged_tags,
+            &default,
+            None,
+            None,
+            false,
+            on_no_commits,
+            None,
+        );
+        match expected_result {
+            Ok(expected_version) => {
+                assert_eq!(
+                    expected_version.map(String::from),
+                    result?.map(|v| v.to_string())
+                );
+            }
+            Err(()) => assert!(result.is_err()),
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(None, &[])]
+    #[case(None, &["not-a-version"])]
+    #[case(Some("v1.2.3"), &["v1.2.3"])]
+    #[case(Some("v1.10.0"), &["v1.2.3", "v1.10.0", "not-a-version", "v1.9.0"])]
+    fn highest_version_basics(#[case] expected_result: Option<&str>, #[case] tags: &[&str]) {
+        let tags = tags.iter().map(|t| String::from(*t)).collect::<Vec<_>>();
+        assert_eq!(
+            expected_result.map(String::from),
+            highest_version(&tags, None).map(|v| v.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(Some("## 1.2.3\n"), Some("## 1.2.3\n"), true)]
+    #[case(Some("## 1.2.3\n"), Some("## 1.2.3\n"), false)]
+    #[case(Some("Release notes\n"), None, true)]
+    #[case(None, None, false)]
+    fn release_notes_basics(
+        #[case] expected_result: Option<&str>,
+        #[case] changelog_section: Option<&str>,
+        #[case] has_tag_message_file: bool,
+    ) -> Result<()> {
+        let test_dir = TestDir::new(&format!(
+            "release-notes-{}-{has_tag_message_file}",
+            changelog_section.is_some()
+        ))?;
+        let tag_message_path = test_dir.path().join("TAG_MESSAGE");
+        write(&tag_message_path, "Release notes\n")?;
+        assert_eq!(
+            expected_result.map(String::from),
+            release_notes(
+                changelog_section,
+                has_tag_message_file.then_some(tag_message_path.as_path())
+            )?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("2024-01-02", "2024-01-02T23:30:00-05:00")]
+    #[case("2024-01-03", "2024-01-03T00:30:00+01:00")]
+    fn changelog_date_from_commit_date(#[case] expected_result: &str, #[case] commit_date: &str) {
+        assert_eq!(
+            expected_result,
+            changelog_date(Some(
+                DateTime::parse_from_rfc3339(commit_date).expect("must parse")
+            ))
+            .to_string()
+        );
+    }
+
+    #[rstest]
+    #[case(None, None, &[])]
+    #[case(Some("v1.2.3"), Some("v1.2.3-2-gabcdef0"), &[])]
+    #[case(Some("v1.2.3"), Some("v1.2.3-2-gabcdef0"), &["v1.2.3"])]
+    #[case(
+        Some("v1.10.0"),
+        Some("v1.9.0-2-gabcdef0"),
+        &["v1.9.0", "v1.10.0", "not-a-version"]
+    )]
+    fn version_base_tag_basics(
+        #[case] expected_result: Option<&str>,
+        #[case] description: Option<&str>,
+        #[case] merged_tags: &[&str],
+    ) {
+        let description = description.and_then(GitDescription::parse);
+        let merged_tags = merged_tags
+            .iter()
+            .map(|t| String::from(*t))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expected_result,
+            version_base_tag(description.as_ref(), &merged_tags, None)
+        );
+    }
+
+    #[rstest]
+    #[case(&[], &[])]
+    #[case(&["v1.2.3"], &["v1.2.3", "not-a-version"])]
+    #[case(
+        &["v1.2.3", "v1.9.0", "1.10.0", "v2.0.0-rc.1", "v2.0.0"],
+        &["v2.0.0", "1.10.0", "v1.2.3", "v2.0.0-rc.1", "nightly", "v1.9.0"]
+    )]
+    fn sorted_tag_versions_basics(#[case] expected_result: &[&str], #[case] tags: &[&str]) {
+        let tags = tags.iter().map(|t| String::from(*t)).collect::<Vec<_>>();
+        assert_eq!(
+            expected_result,
+            sorted_tag_versions(&tags, None)
+                .into_iter()
+                .map(|(_, tag)| tag)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case(&[], "v1.3.0", &["v1.2.3", "v1.2.4"])]
+    #[case(&["v1.3.0"], "v1.3.0", &["v1.2.3", "v1.3.0"])]
+    #[case(&["1.3.0", "v1.4.0"], "v1.3.0", &["v1.2.3", "1.3.0", "v1.4.0"])]
+    #[case(&[], "v1.3.0", &["v1.3.0-rc.1"])]
+    fn out_of_order_tags_basics(
+        #[case] expected_result: &[&str],
+        #[case] new_version: &str,
+        #[case] tags: &[&str],
+    ) -> Result<()> {
+        let tags = tags.iter().map(|t| String::from(*t)).collect::<Vec<_>>();
+        assert_eq!(
+            expected_result,
+            out_of_order_tags(
+                &sorted_tag_versions(&tags, None),
+                &new_version.parse::<Version>()?
+            )
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("v0.0.0", None, &[])]
+    #[case("v1.2.4", Some("v1.2.3-2-gabcdef0"), &["v1.2.3"])]
+    #[case(
+        "v1.3.1",
+        Some("v1.2.3-2-gabcdef0"),
+        &["v1.0.0", "v1.3.0", "v1.2.3"]
+    )]
+    #[case(
+        "v1.2.4",
+        Some("v1.2.3-5-gabcdef0"),
+        &["nightly", "v1.2.3", "deploy-2024"]
+    )]
+    fn next_version_merged_tags(
+        #[case] expected_result: &str,
+        #[case] description: Option<&str>,
+        #[case] merged_tags: &[&str],
+    ) -> Result<()> {
+        let default = "v0.0.0".parse::<Version>()?;
+        let merged_tags = merged_tags
+            .iter()
+            .map(|t| String::from(*t))
+            .collect::<Vec<_>>();
+        let result = next_version(
+            description.and_then(GitDescription::parse),
+            &merged_tags,
+            &default,
+            None,
+            None,
+            false,
+            OnNoCommits::Error,
+            None,
+        )?;
+        assert_eq!(
+            Some(String::from(expected_result)),
+            result.map(|v| v.to_string())
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("v2.0.0", Bump::Major, false, &["v1.2.3"])]
+    #[case("v1.3.0", Bump::Minor, false, &["v1.2.3"])]
+    #[case("v1.2.4", Bump::Patch, false, &["v1.2.3"])]
+    #[case("v1.3.0", Bump::Minor, false, &["v1.2.3-rc.1"])]
+    #[case("v0.0.0", Bump::Major, false, &[])]
+    #[case("v1.0.0", Bump::Major, false, &["v0.4.2"])]
+    #[case("v0.5.0", Bump::Major, true, &["v0.4.2"])]
+    #[case("v0.4.3", Bump::Minor, true, &["v0.4.2"])]
+    #[case("v0.4.3", Bump::Patch, true, &["v0.4.2"])]
+    #[case("v2.0.0", Bump::Major, true, &["v1.2.3"])]
+    fn next_version_bump(
+        #[case] expected_result: &str,
+        #[case] bump: Bump,
+        #[case] zerover: bool,
+        #[case] merged_tags: &[&str],
+    ) -> Result<()> {
+        let default = "v0.0.0".parse::<Version>()?;
+        let merged_tags = merged_tags
+            .iter()
+            .map(|t| String::from(*t))
+            .collect::<Vec<_>>();
+        let result = next_version(
+            None,
+            &merged_tags,
+            &default,
+            None,
+            Some(bump),
+            zerover,
+            OnNoCommits::Error,
+            None,
+        )?;
+        assert_eq!(
+            Some(String::from(expected_result)),
+            result.map(|v| v.to_string())
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("v0.4.1", None, "0.4.0", &[])]
+    #[case("v1.0.0", Some(Bump::Major), "0.4.0", &[])]
+    #[case("v0.0.0", None, "0.0.0", &[])]
+    #[case("v1.2.4", None, "0.4.0", &["v1.2.3"])]
+    fn next_version_manifest_version(
+        #[case] expected_result: &str,
+        #[case] bump: Option<Bump>,
+        #[case] manifest_version: &str,
+        #[case] merged_tags: &[&str],
+    ) -> Result<()> {
+        let default = "v0.0.0".parse::<Version>()?;
+        let merged_tags = merged_tags
+            .iter()
+            .map(|t| String::from(*t))
+            .collect::<Vec<_>>();
+        let result = next_version(
+            None,
+            &merged_tags,
+            &default,
+            Some(manifest_version.parse::<Version>()?),
+            bump,
+            false,
+            OnNoCommits::Error,
+            None,
+        )?;
+        assert_eq!(
+            Some(String::from(expected_result)),
+            result.map(|v| v.to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn next_version_bump_missing_component() -> Result<()> {
+        let default = "v0.0.0".parse::<Version>()?;
+        let result = next_version(
+            None,
+            &[String::from("v1")],
+            &default,
+            None,
+            Some(Bump::Minor),
+            false,
+            OnNoCommits::Error,
+            None,
+        );
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(&["v[0-9]*", "[0-9]*"], None, None)]
+    #[case(&["release-*"], Some("release-*"), None)]
+    #[case(&["release-*"], Some("release-*"), Some("{prefix}{version}"))]
+    #[case(&["release/*"], None, Some("release/{version}"))]
+    #[case(&["*"], None, Some("{prefix}{version}"))]
+    #[case(&["app-*.*-stable"], None, Some("app-{major}.{minor}-stable"))]
+    fn match_patterns_basics(
+        #[case] expected_result: &[&str],
+        #[case] tag_match: Option<&str>,
+        #[case] tag_template: Option<&str>,
+    ) -> Result<()> {
+        let tag_template = tag_template.map(TagTemplate::new).transpose()?;
+        assert_eq!(
+            expected_result
+                .iter()
+                .map(|s| String::from(*s))
+                .collect::<Vec<_>>(),
+            match_patterns(tag_match.map(String::from), tag_template.as_ref())
+        );
+        Ok(())
+    }
 }
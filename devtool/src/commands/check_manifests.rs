@@ -0,0 +1,192 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::app::App;
+use crate::error::DevtoolResult;
+use crate::manifests::{ManifestKind, ManifestUpdate, ManifestUpdater};
+use crate::project_info::ProjectInfo;
+use anyhow::{anyhow, Result};
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+pub fn check_manifests(app: &App) -> DevtoolResult<()> {
+    let project_info = ProjectInfo::read(app, app.read_config()?.as_ref(), None)?;
+    let lines = manifest_diffs(&project_info.manifests())?;
+    if lines.is_empty() {
+        println!("Manifests are up to date");
+        return Ok(());
+    }
+
+    for line in lines {
+        println!("{line}");
+    }
+    Err(anyhow!("Manifests are not in the state devtool would produce").into())
+}
+
+// Diffs between each manifest and its content after setting the highest
+// version found in any manifest
+fn manifest_diffs(manifests: &[(PathBuf, ManifestKind)]) -> Result<Vec<String>> {
+    let mut versions = Vec::new();
+    for (path, kind) in manifests {
+        if let Some(version) = kind.read_version(path)? {
+            versions.push(version);
+        }
+    }
+
+    let Some(mut version) = versions.into_iter().max() else {
+        return Ok(Vec::new());
+    };
+    version.set_prefix(false);
+
+    let mut lines = Vec::new();
+    for (path, kind) in manifests {
+        for (path, update) in kind.plan_version(path, &version)? {
+            if let ManifestUpdate::Changed(updated) = update {
+                let original = if path.is_file() {
+                    read_to_string(&path)?
+                } else {
+                    String::new()
+                };
+                lines.push(format!("--- {}", path.display()));
+                lines.push(format!("+++ {}", path.display()));
+                lines.extend(line_diff(&original, &updated));
+            }
+        }
+    }
+
+    Ok(lines)
+}
+
+// Longest common subsequence diff so that an inserted line is reported on
+// its own instead of shifting every line after it
+fn line_diff(original: &str, updated: &str) -> Vec<String> {
+    let original = original.lines().collect::<Vec<_>>();
+    let updated = updated.lines().collect::<Vec<_>>();
+
+    // lengths[i][j] is the length of the longest common subsequence of
+    // original[i..] and updated[j..]
+    let mut lengths = vec![vec![0usize; updated.len() + 1]; original.len() + 1];
+    for (i, a) in original.iter().enumerate().rev() {
+        for (j, b) in updated.iter().enumerate().rev() {
+            lengths[i][j] = if a == b {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < original.len() || j < updated.len() {
+        if i < original.len() && j < updated.len() && original[i] == updated[j] {
+            i += 1;
+            j += 1;
+        } else if i < original.len()
+            && (j == updated.len() || lengths[i + 1][j] >= lengths[i][j + 1])
+        {
+            lines.push(format!("-{}", original[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", updated[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{line_diff, manifest_diffs};
+    use crate::manifests::ManifestKind;
+    use crate::test_dir::TestDir;
+    use anyhow::Result;
+    use rstest::rstest;
+    use std::fs::write;
+
+    #[rstest]
+    #[case(&[], "a\nb\n", "a\nb\n")]
+    #[case(&["-b", "+c"], "a\nb\n", "a\nc\n")]
+    #[case(&["+c"], "a\n", "a\nc\n")]
+    #[case(&["-b"], "a\nb\n", "a\n")]
+    #[case(&["+c"], "a\nb\n", "a\nc\nb\n")]
+    #[case(&["-b"], "a\nb\nc\n", "a\nc\n")]
+    #[case(&["-b", "+x", "+y"], "a\nb\nc\n", "a\nx\ny\nc\n")]
+    #[case(
+        &["+version = \"1.2.3\""],
+        "[package]\nname = \"foo\"\n\n[dependencies]\nanyhow = \"1\"\n",
+        "[package]\nname = \"foo\"\nversion = \"1.2.3\"\n\n[dependencies]\nanyhow = \"1\"\n"
+    )]
+    fn line_diff_basics(
+        #[case] expected_result: &[&str],
+        #[case] original: &str,
+        #[case] updated: &str,
+    ) {
+        assert_eq!(expected_result, line_diff(original, updated));
+    }
+
+    #[rstest]
+    #[case(
+        "passing",
+        &[],
+        "[package]\nname = \"foo\"\nversion = \"1.2.3\"\n",
+        "[project]\nname = \"bar\"\nversion = \"1.2.3\"\n"
+    )]
+    #[case(
+        "inconsistent",
+        &["-version = \"1.2.0\"", "+version = \"1.2.3\""],
+        "[package]\nname = \"foo\"\nversion = \"1.2.3\"\n",
+        "[project]\nname = \"bar\"\nversion = \"1.2.0\"\n"
+    )]
+    #[case(
+        "non-canonical",
+        &["-version = \"v1.2.3\"", "+version = \"1.2.3\""],
+        "[package]\nname = \"foo\"\nversion = \"1.2.3\"\n",
+        "[project]\nname = \"bar\"\nversion = \"v1.2.3\"\n"
+    )]
+    fn manifest_diffs_basics(
+        #[case] name: &str,
+        #[case] expected_result: &[&str],
+        #[case] cargo_toml: &str,
+        #[case] pyproject_toml: &str,
+    ) -> Result<()> {
+        let test_dir = TestDir::new(&format!("check-manifests-{name}"))?;
+        let dir = test_dir.path();
+        let cargo_toml_path = dir.join("Cargo.toml");
+        let pyproject_toml_path = dir.join("pyproject.toml");
+        write(&cargo_toml_path, cargo_toml)?;
+        write(&pyproject_toml_path, pyproject_toml)?;
+
+        let result = manifest_diffs(&[
+            (cargo_toml_path, ManifestKind::Cargo),
+            (pyproject_toml_path.clone(), ManifestKind::Pyproject),
+        ]);
+
+        let mut expected_lines = Vec::new();
+        if !expected_result.is_empty() {
+            expected_lines.push(format!("--- {}", pyproject_toml_path.display()));
+            expected_lines.push(format!("+++ {}", pyproject_toml_path.display()));
+            expected_lines.extend(expected_result.iter().map(|s| String::from(*s)));
+        }
+        assert_eq!(expected_lines, result?);
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::args::Args;
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use std::io::{stdout, Write};
+
+pub fn completions(shell: Shell) {
+    write_completions(shell, &mut stdout());
+}
+
+fn write_completions<W: Write>(shell: Shell, writer: &mut W) {
+    let mut command = Args::command();
+    let name = command.get_name().to_string();
+    generate(shell, &mut command, name, writer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_completions;
+    use anyhow::Result;
+    use clap_complete::Shell;
+
+    #[test]
+    fn bash() -> Result<()> {
+        let mut output = Vec::new();
+        write_completions(Shell::Bash, &mut output);
+        let s = String::from_utf8(output)?;
+        assert!(s.contains("bump-version"));
+        assert!(s.contains("completions"));
+        assert!(s.contains("show-description"));
+        Ok(())
+    }
+}
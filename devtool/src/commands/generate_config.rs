@@ -20,10 +20,181 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 use crate::app::App;
+use crate::error::DevtoolResult;
+use crate::project_info::ProjectInfo;
 use crate::serialization::Config;
 use anyhow::Result;
+use std::path::{Path, PathBuf};
 
-pub fn generate_config(app: &App) -> Result<()> {
-    app.write_config(&Config::default(), false)?;
+const OPTIONAL_KEYS: &str = r#"
+# Optional settings: uncomment and edit as required
+# tag_template: "{prefix}{version}"
+# tag_match: "v*"
+# tag_prefix: true # always (true) or never (false) prefix tags with "v"
+# commit_message_template: "Bump version to {version}"
+# zerover: true # breaking changes bump minor and features bump patch while major is 0
+# dirty_tree_policy: error # error, warn or ignore
+# push: true
+# allowed_branches: # branch names or glob patterns (default: main and master)
+#   - main
+#   - release/*
+# version_manifest:
+#   path: versions.toml
+#   table: versions
+# version_keys:
+#   - package.metadata.*.version
+# version_key_excludes:
+#   - package.metadata.internal.version
+"#;
+
+pub fn generate_config(app: &App, dry_run: bool) -> DevtoolResult<()> {
+    let content = render_config(&build_config(
+        &ProjectInfo::read(app, None, None)?,
+        &app.git.dir,
+    ))?;
+    if dry_run {
+        print!("{content}");
+        return Ok(());
+    }
+
+    let config_path = app.config_path();
+    if config_path.exists() {
+        println!(
+            "Configuration file {} already exists: remove it first to generate a new one",
+            config_path.display()
+        );
+        return Ok(());
+    }
+
+    app.write_config(&content, false)?;
+    println!("Wrote configuration file {}", config_path.display());
     Ok(())
 }
+
+fn render_config(config: &Config) -> Result<String> {
+    Ok(format!("{}{OPTIONAL_KEYS}", serde_yaml::to_string(config)?))
+}
+
+fn build_config(project_info: &ProjectInfo, dir: &Path) -> Config {
+    let relative = |paths: &[PathBuf]| {
+        paths
+            .iter()
+            .map(|p| {
+                p.strip_prefix(dir)
+                    .map_or_else(|_| p.clone(), Path::to_path_buf)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    Config {
+        cargo_toml_paths: relative(&project_info.cargo_toml_paths),
+        pyproject_toml_paths: relative(&project_info.pyproject_toml_paths),
+        gradle_paths: relative(&project_info.gradle_paths),
+        package_json_paths: relative(&project_info.package_json_paths),
+        setup_cfg_paths: relative(&project_info.setup_cfg_paths),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_config, generate_config, render_config};
+    use crate::app::App;
+    use crate::project_info::ProjectInfo;
+    use crate::serialization::Config;
+    use crate::test_dir::TestDir;
+    use anyhow::Result;
+    use std::fs::{read_to_string, write};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn build_config_relative_paths() -> Result<()> {
+        let dir = Path::new("/project");
+        let project_info = ProjectInfo {
+            cargo_toml_paths: vec![dir.join("Cargo.toml"), dir.join("foo").join("Cargo.toml")],
+            pyproject_toml_paths: vec![PathBuf::from("/other/pyproject.toml")],
+            gradle_paths: vec![dir.join("gradle.properties")],
+            package_json_paths: vec![dir.join("web").join("package.json")],
+            setup_cfg_paths: vec![dir.join("setup.cfg")],
+            setup_py_paths: vec![dir.join("setup.py")],
+        };
+
+        let config = serde_yaml::from_str::<Config>(&serde_yaml::to_string(&build_config(
+            &project_info,
+            dir,
+        ))?)?;
+        assert_eq!(
+            vec![
+                PathBuf::from("Cargo.toml"),
+                Path::new("foo").join("Cargo.toml")
+            ],
+            config.cargo_toml_paths
+        );
+        assert_eq!(
+            vec![PathBuf::from("/other/pyproject.toml")],
+            config.pyproject_toml_paths
+        );
+        assert_eq!(
+            vec![PathBuf::from("gradle.properties")],
+            config.gradle_paths
+        );
+        assert_eq!(
+            vec![Path::new("web").join("package.json")],
+            config.package_json_paths
+        );
+        assert_eq!(vec![PathBuf::from("setup.cfg")], config.setup_cfg_paths);
+        Ok(())
+    }
+
+    #[test]
+    fn render_config_includes_optional_keys() -> Result<()> {
+        let content = render_config(&Config {
+            cargo_toml_paths: vec![PathBuf::from("Cargo.toml")],
+            ..Default::default()
+        })?;
+
+        assert!(content.contains("# tag_template: \"{prefix}{version}\"\n"));
+        assert!(content.contains("# version_keys:\n"));
+
+        let config = serde_yaml::from_str::<Config>(&content)?;
+        assert_eq!(vec![PathBuf::from("Cargo.toml")], config.cargo_toml_paths);
+        assert!(config.tag_template.is_none());
+        assert!(config.version_keys.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn existing_config_is_not_overwritten() -> Result<()> {
+        let test_dir = TestDir::new("generate-config-existing")?;
+        write(
+            test_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\n",
+        )?;
+
+        let app = App::new(test_dir.path());
+        write(app.config_path(), "push: false\n")?;
+        let result = generate_config(&app, false);
+        let content = read_to_string(app.config_path());
+
+        result?;
+        assert_eq!("push: false\n", content?);
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_writes_no_file() -> Result<()> {
+        let test_dir = TestDir::new("generate-config")?;
+        write(
+            test_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\n",
+        )?;
+
+        let app = App::new(test_dir.path());
+        let result = generate_config(&app, true);
+        let config_exists = app.config_path().exists();
+
+        result?;
+        assert!(!config_exists);
+        Ok(())
+    }
+}
@@ -20,21 +20,56 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 use crate::app::App;
-use anyhow::Result;
+use crate::constants::GIT_IGNORE_FILE_NAME;
+use crate::error::DevtoolResult;
+use crate::project_info::ProjectInfo;
+use anyhow::{bail, Result};
+use devtool_git::StatusEntry;
+use joatmon::safe_write_file;
+use std::fs::read_to_string;
+use std::path::Path;
 
-const UNTRACKED_PREFIX: &str = "?? ";
-const IGNORED_PREFIX: &str = "!! ";
+const BEGIN_MARKER: &str = "# BEGIN devtool";
+const END_MARKER: &str = "# END devtool";
 
-pub fn generate_ignore(app: &App) -> Result<()> {
-    let s = app.git.status(false)?;
+#[allow(clippy::fn_params_excessive_bools)]
+pub fn generate_ignore(
+    app: &App,
+    write: bool,
+    force: bool,
+    append: bool,
+    templates: bool,
+) -> DevtoolResult<()> {
+    let mut content = render_ignore(&app.git.status_ignored()?);
+    if templates {
+        content = render_templates(&ProjectInfo::infer(app, None)?, &content) + &content;
+    }
 
+    if write {
+        write_ignore(
+            &app.git.dir.join(GIT_IGNORE_FILE_NAME),
+            &content,
+            force,
+            append,
+        )?;
+    } else {
+        print!("{content}");
+    }
+    Ok(())
+}
+
+fn render_ignore(status_entries: &[StatusEntry]) -> String {
     let mut all_dir_paths = Vec::new();
     let mut all_file_paths = Vec::new();
-    for line in s.lines().filter_map(is_path_to_ignore) {
-        if line.ends_with('/') {
-            all_dir_paths.push(line);
+    for path in status_entries
+        .iter()
+        .filter(|e| e.is_untracked() || e.is_ignored())
+        .map(|e| e.path.as_str())
+    {
+        if path.ends_with('/') {
+            all_dir_paths.push(path);
         } else {
-            all_file_paths.push(line);
+            all_file_paths.push(path);
         }
     }
 
@@ -55,31 +90,150 @@ pub fn generate_ignore(app: &App) -> Result<()> {
         }
     }
 
+    let mut lines = Vec::new();
     if !dir_paths.is_empty() {
-        println!("# Directories");
-        for p in &dir_paths {
-            println!("/{p}");
-        }
+        lines.push(String::from("# Directories"));
+        lines.extend(dir_paths.iter().map(|p| format!("/{}", escape_pattern(p))));
     }
 
     if !file_paths.is_empty() {
-        println!("# Files");
-        for p in &file_paths {
-            println!("/{p}");
+        lines.push(String::from("# Files"));
+        lines.extend(file_paths.iter().map(|p| format!("/{}", escape_pattern(p))));
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+// Standard entries for each detected project type, omitting any entry
+// already produced from the scanned paths
+fn render_templates(project_info: &ProjectInfo, content: &str) -> String {
+    let blocks: [(&str, bool, &[&str]); 4] = [
+        (
+            "Rust",
+            !project_info.cargo_toml_paths.is_empty(),
+            &["/target/"],
+        ),
+        (
+            "Python",
+            !project_info.pyproject_toml_paths.is_empty()
+                || !project_info.setup_cfg_paths.is_empty()
+                || !project_info.setup_py_paths.is_empty(),
+            &["__pycache__/", ".venv/"],
+        ),
+        (
+            "Node",
+            !project_info.package_json_paths.is_empty(),
+            &["node_modules/"],
+        ),
+        (
+            "Gradle",
+            !project_info.gradle_paths.is_empty(),
+            &[".gradle/", "build/"],
+        ),
+    ];
+
+    let existing_lines = content.lines().collect::<Vec<_>>();
+    let mut lines = Vec::new();
+    for (name, detected, entries) in blocks {
+        if !detected {
+            continue;
+        }
+
+        let entries = entries
+            .iter()
+            .filter(|e| {
+                !existing_lines.contains(e) && !existing_lines.contains(&format!("/{e}").as_str())
+            })
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            continue;
         }
+
+        lines.push(format!("# {name}"));
+        lines.extend(entries.iter().map(|e| String::from(**e)));
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+fn write_ignore(path: &Path, content: &str, force: bool, append: bool) -> Result<()> {
+    let section = format!("{BEGIN_MARKER}\n{content}{END_MARKER}\n");
+
+    if append && path.is_file() {
+        let existing = read_to_string(path)?;
+        safe_write_file(path, replace_managed_section(&existing, &section), true)?;
+        return Ok(());
+    }
+
+    if force || !path.exists() {
+        safe_write_file(path, section, force)?;
+        return Ok(());
+    }
+
+    if !path.is_file() {
+        bail!("{} exists but is not a file", path.display())
     }
 
+    let existing = read_to_string(path)?;
+    let merged = merge_entries(&existing, content);
+    if merged != existing {
+        safe_write_file(path, merged, true)?;
+    }
     Ok(())
 }
 
-fn is_path_to_ignore(line: &str) -> Option<&str> {
-    if let Some(s) = line.strip_prefix(UNTRACKED_PREFIX) {
-        Some(s)
-    } else if let Some(s) = line.strip_prefix(IGNORED_PREFIX) {
-        Some(s)
+// Appends only those entries not already present in the file, leaving
+// existing lines, including comments, in their original order
+fn merge_entries(existing: &str, content: &str) -> String {
+    let existing_lines = existing.lines().map(str::trim).collect::<Vec<_>>();
+    let new_entries = content
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !existing_lines.contains(line))
+        .collect::<Vec<_>>();
+    if new_entries.is_empty() {
+        return String::from(existing);
+    }
+
+    let mut merged = String::from(existing);
+    if !merged.is_empty() && !merged.ends_with('\n') {
+        merged.push('\n');
+    }
+    for entry in new_entries {
+        merged.push_str(entry);
+        merged.push('\n');
+    }
+    merged
+}
+
+// Replaces the managed section in place or adds one to the end of the file
+fn replace_managed_section(existing: &str, section: &str) -> String {
+    if let Some((before, rest)) = existing.split_once(BEGIN_MARKER) {
+        if let Some((_, after)) = rest.split_once(END_MARKER) {
+            let after = after.strip_prefix('\n').unwrap_or(after);
+            return format!("{before}{section}{after}");
+        }
+    }
+
+    if existing.is_empty() || existing.ends_with('\n') {
+        format!("{existing}{section}")
     } else {
-        None
+        format!("{existing}\n{section}")
+    }
+}
+
+// Escape characters that .gitignore would otherwise treat as wildcards or
+// strip as trailing whitespace so that the entry matches the path literally
+fn escape_pattern(path: &str) -> String {
+    let trimmed = path.trim_end_matches(' ');
+    let mut result = String::new();
+    for c in trimmed.chars() {
+        if matches!(c, '\\' | '*' | '?' | '[') {
+            result.push('\\');
+        }
+        result.push(c);
     }
+    result.push_str(&"\\ ".repeat(path.len() - trimmed.len()));
+    result
 }
 
 fn is_covered_by_dir<S>(dir_paths: &Vec<S>, path: &str) -> bool
@@ -93,3 +247,121 @@ where
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        escape_pattern, merge_entries, render_ignore, render_templates, replace_managed_section,
+        write_ignore,
+    };
+    use crate::project_info::ProjectInfo;
+    use crate::test_dir::TestDir;
+    use anyhow::Result;
+    use devtool_git::StatusEntry;
+    use rstest::rstest;
+    use std::fs::{read_to_string, write};
+    use std::path::PathBuf;
+
+    const SECTION: &str = "# BEGIN devtool\n/target/\n# END devtool\n";
+
+    #[test]
+    fn render_ignore_basics() {
+        assert_eq!(
+            "# Directories\n/target/\n# Files\n/my file.txt\n/notes.txt\n",
+            render_ignore(&StatusEntry::parse_porcelain_v2(concat!(
+                "? target/\0",
+                "! target/debug/\0",
+                "? notes.txt\0",
+                "? my file.txt\0",
+                "1 .M N... 100644 100644 100644 0123456 0123456 src/main.rs\0"
+            )))
+        );
+    }
+
+    #[rstest]
+    #[case("my file.txt", "my file.txt")]
+    #[case("caf\u{e9}.txt", "caf\u{e9}.txt")]
+    #[case("what\\?.txt", "what?.txt")]
+    #[case("\\[draft]\\*.md", "[draft]*.md")]
+    #[case("notes\\ \\ ", "notes  ")]
+    fn escape_pattern_basics(#[case] expected_result: &str, #[case] path: &str) {
+        assert_eq!(expected_result, escape_pattern(path));
+    }
+
+    #[rstest]
+    #[case("# Rust\n/target/\n# Node\nnode_modules/\n", "")]
+    #[case("# Node\nnode_modules/\n", "# Directories\n/target/\n")]
+    #[case("# Rust\n/target/\n", "# Directories\n/node_modules/\n")]
+    fn render_templates_basics(#[case] expected_result: &str, #[case] content: &str) {
+        let project_info = ProjectInfo {
+            cargo_toml_paths: vec![PathBuf::from("/project/Cargo.toml")],
+            pyproject_toml_paths: Vec::new(),
+            gradle_paths: Vec::new(),
+            package_json_paths: vec![PathBuf::from("/project/web/package.json")],
+            setup_cfg_paths: Vec::new(),
+            setup_py_paths: Vec::new(),
+        };
+        assert_eq!(expected_result, render_templates(&project_info, content));
+    }
+
+    #[rstest]
+    #[case(SECTION, "")]
+    #[case("/local\n# BEGIN devtool\n/target/\n# END devtool\n", "/local\n")]
+    #[case("/local\n# BEGIN devtool\n/target/\n# END devtool\n", "/local")]
+    #[case(
+        "/a\n# BEGIN devtool\n/target/\n# END devtool\n/b\n",
+        "/a\n# BEGIN devtool\n/old/\n# END devtool\n/b\n"
+    )]
+    fn replace_managed_section_basics(#[case] expected_result: &str, #[case] existing: &str) {
+        assert_eq!(expected_result, replace_managed_section(existing, SECTION));
+    }
+
+    #[rstest]
+    #[case("/local\n", "/local\n", "# Directories\n/local\n")]
+    #[case("", "", "")]
+    #[case("/target/\n", "", "# Directories\n/target/\n")]
+    #[case(
+        "# Mine\n/local\n/target/\n/notes.txt\n",
+        "# Mine\n/local",
+        "# Directories\n/target/\n# Files\n/local\n/notes.txt\n"
+    )]
+    #[case(
+        "/b/\n# Keep\n/a/\n/c/\n",
+        "/b/\n# Keep\n/a/\n",
+        "# Directories\n/a/\n/b/\n/c/\n"
+    )]
+    #[case("  /target/  \n", "  /target/  \n", "# Directories\n/target/\n")]
+    #[case(
+        "/local\nnode_modules/\n",
+        "/local\n",
+        "# Node\nnode_modules/\n# Files\n/local\n"
+    )]
+    fn merge_entries_basics(
+        #[case] expected_result: &str,
+        #[case] existing: &str,
+        #[case] content: &str,
+    ) {
+        assert_eq!(expected_result, merge_entries(existing, content));
+    }
+
+    #[rstest]
+    #[case("/local\n/target/\n", false, false)]
+    #[case(SECTION, true, false)]
+    #[case("/local\n# BEGIN devtool\n/target/\n# END devtool\n", false, true)]
+    fn write_ignore_existing_file(
+        #[case] expected_result: &str,
+        #[case] force: bool,
+        #[case] append: bool,
+    ) -> Result<()> {
+        let test_dir = TestDir::new(&format!("write-ignore-{force}-{append}"))?;
+        let path = test_dir.path().join(".gitignore");
+        write(&path, "/local\n")?;
+
+        let result = write_ignore(&path, "/target/\n", force, append);
+        let contents = read_to_string(&path)?;
+
+        result?;
+        assert_eq!(expected_result, contents);
+        Ok(())
+    }
+}
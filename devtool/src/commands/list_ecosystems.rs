@@ -0,0 +1,49 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::app::App;
+use crate::error::DevtoolResult;
+use crate::project_info::ProjectInfo;
+use crate::table::Table;
+
+pub fn list_ecosystems(app: &App) -> DevtoolResult<()> {
+    let ecosystems = ProjectInfo::read(app, app.read_config()?.as_ref(), None)?.ecosystems();
+
+    println!("Supported ecosystems:");
+    let mut table = Table::default();
+    for ecosystem in &ecosystems {
+        table.add_row(ecosystem.name, ecosystem.file_name);
+    }
+    table.print();
+
+    println!("Detected in {}:", app.git.dir.display());
+    if ecosystems.iter().all(|e| e.count == 0) {
+        println!("(none)");
+    } else {
+        let mut table = Table::default();
+        for ecosystem in ecosystems.iter().filter(|e| e.count > 0) {
+            table.add_row(ecosystem.name, format!("{} file(s)", ecosystem.count));
+        }
+        table.print();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use super::bump_version::sorted_tag_versions;
+use crate::app::App;
+use crate::error::DevtoolResult;
+use crate::table::Table;
+use crate::tag_template::TagTemplate;
+use devtool_version::Version;
+
+pub fn list_tags(app: &App) -> DevtoolResult<()> {
+    let tag_template = app
+        .read_config()?
+        .and_then(|c| c.tag_template)
+        .map(TagTemplate::new)
+        .transpose()?;
+
+    let tags = app.git.list_tags()?;
+    let (versions, others) = split_tags(&tags, tag_template.as_ref());
+
+    if versions.is_empty() {
+        println!("(no version tags)");
+    } else {
+        let mut table = Table::default();
+        let latest = versions.len() - 1;
+        for (i, (version, tag)) in versions.iter().enumerate() {
+            let value = if i == latest {
+                let count = app.git.count_commits(tag, "HEAD")?;
+                format!(
+                    "{version} (latest, {count} commit{} since tag)",
+                    if count == 1 { "" } else { "s" }
+                )
+            } else {
+                version.to_string()
+            };
+            table.add_row(*tag, value);
+        }
+        table.print();
+    }
+
+    if !others.is_empty() {
+        println!();
+        println!("Non-version tags:");
+        for tag in others {
+            println!("{tag}");
+        }
+    }
+
+    Ok(())
+}
+
+// Version tags in ascending order and the remaining tags in their original order
+fn split_tags<'a>(
+    tags: &'a [String],
+    tag_template: Option<&TagTemplate>,
+) -> (Vec<(Version, &'a str)>, Vec<&'a str>) {
+    let versions = sorted_tag_versions(tags, tag_template);
+    let others = tags
+        .iter()
+        .map(String::as_str)
+        .filter(|tag| !versions.iter().any(|(_, t)| t == tag))
+        .collect();
+    (versions, others)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_tags;
+    use crate::tag_template::TagTemplate;
+    use anyhow::Result;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[], &[], &[], None)]
+    #[case(
+        &["v1.2.3", "v1.10.0"],
+        &["nightly", "deploy-2024"],
+        &["v1.10.0", "nightly", "v1.2.3", "deploy-2024"],
+        None
+    )]
+    #[case(
+        &["release/1.2.3", "release/1.3.0"],
+        &["v2.0.0"],
+        &["release/1.3.0", "v2.0.0", "release/1.2.3"],
+        Some("release/{version}")
+    )]
+    fn split_tags_basics(
+        #[case] expected_versions: &[&str],
+        #[case] expected_others: &[&str],
+        #[case] tags: &[&str],
+        #[case] tag_template: Option<&str>,
+    ) -> Result<()> {
+        let tags = tags.iter().map(|t| String::from(*t)).collect::<Vec<_>>();
+        let tag_template = tag_template.map(TagTemplate::new).transpose()?;
+        let (versions, others) = split_tags(&tags, tag_template.as_ref());
+        assert_eq!(
+            expected_versions,
+            versions.into_iter().map(|(_, t)| t).collect::<Vec<_>>()
+        );
+        assert_eq!(expected_others, others);
+        Ok(())
+    }
+}
@@ -20,13 +20,27 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 mod bump_version;
+mod check_manifests;
+mod completions;
 mod generate_config;
 mod generate_ignore;
+mod list_ecosystems;
+mod list_tags;
+mod prune_tags;
+mod resume;
 mod scratch;
 mod show_description;
+mod undo_bump;
 
-pub use self::bump_version::bump_version;
+pub use self::bump_version::{bump_version, BumpVersionOptions};
+pub use self::check_manifests::check_manifests;
+pub use self::completions::completions;
 pub use self::generate_config::generate_config;
 pub use self::generate_ignore::generate_ignore;
+pub use self::list_ecosystems::list_ecosystems;
+pub use self::list_tags::list_tags;
+pub use self::prune_tags::prune_tags;
+pub use self::resume::resume;
 pub use self::scratch::scratch;
-pub use self::show_description::show_description;
+pub use self::show_description::{show_description, ShowDescriptionOptions};
+pub use self::undo_bump::undo_bump;
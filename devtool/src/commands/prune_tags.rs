@@ -0,0 +1,86 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::app::App;
+use crate::error::DevtoolResult;
+use std::collections::HashSet;
+
+pub fn prune_tags(app: &App, remote: &str, confirm: bool) -> DevtoolResult<()> {
+    let local_tags = app.git.list_tags()?;
+    let remote_tags = app.git.ls_remote_tags(remote)?;
+    let tags = tags_to_prune(&local_tags, &remote_tags);
+
+    if tags.is_empty() {
+        println!("All local tags are present on remote {remote}");
+        return Ok(());
+    }
+
+    if confirm {
+        for tag in tags {
+            app.git.delete_tag(tag)?;
+            println!("Deleted tag {tag}");
+        }
+    } else {
+        println!("Local tags not present on remote {remote}:");
+        for tag in tags {
+            println!("  {tag}");
+        }
+        println!("Run again with --confirm to delete these tags");
+    }
+
+    Ok(())
+}
+
+fn tags_to_prune<'a>(local_tags: &'a [String], remote_tags: &[String]) -> Vec<&'a str> {
+    let remote_tags = remote_tags.iter().collect::<HashSet<_>>();
+    local_tags
+        .iter()
+        .filter(|tag| !remote_tags.contains(tag))
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tags_to_prune;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Vec::new(), &[], &[])]
+    #[case(Vec::new(), &["v1", "v2"], &["v1", "v2", "v3"])]
+    #[case(vec!["v1", "v3"], &["v1", "v2", "v3"], &["v2"])]
+    #[case(vec!["v1", "v2"], &["v1", "v2"], &[])]
+    fn tags_to_prune_basics(
+        #[case] expected_result: Vec<&str>,
+        #[case] local_tags: &[&str],
+        #[case] remote_tags: &[&str],
+    ) {
+        let local_tags = local_tags
+            .iter()
+            .map(|s| String::from(*s))
+            .collect::<Vec<_>>();
+        let remote_tags = remote_tags
+            .iter()
+            .map(|s| String::from(*s))
+            .collect::<Vec<_>>();
+        assert_eq!(expected_result, tags_to_prune(&local_tags, &remote_tags));
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::app::App;
+use crate::error::DevtoolResult;
+use crate::release_state::ReleaseState;
+use anyhow::anyhow;
+
+pub fn resume(app: &App, rollback: bool) -> DevtoolResult<()> {
+    let Some(state) = ReleaseState::read(&app.git.dir)? else {
+        return Err(anyhow!("No interrupted release found in {}", app.git.dir.display()).into());
+    };
+
+    if rollback {
+        state.rollback(app)?;
+        ReleaseState::remove(&app.git.dir)?;
+        println!("Rolled back release {}", state.tag);
+    } else {
+        let tag = state.tag.clone();
+        state.record(&app.git.dir, |state| state.resume(app))?;
+        println!("Completed release {tag}");
+    }
+
+    Ok(())
+}
@@ -20,20 +20,284 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 use crate::app::App;
-use anyhow::Result;
+use crate::dirty_tree_policy::DirtyTreePolicy;
+use crate::error::DevtoolResult;
+use crate::project_info::ProjectInfo;
+use crate::table::Table;
+use chrono::{DateTime, Utc};
+use devtool_git::{DescribeOptions, GitDescription};
 use devtool_version::Version;
+use serde::Serialize;
+use std::collections::HashMap;
 
-pub fn show_description(app: &App) -> Result<()> {
-    if let Some(description) = app.git.describe()? {
-        println!("description={description:#?}");
-        if let Ok(version) = description.tag.parse::<Version>() {
-            println!("version={version:#?}");
+#[derive(Clone, Copy, Debug)]
+pub struct ShowDescriptionOptions {
+    pub dirty_tree_policy: Option<DirtyTreePolicy>,
+    pub contributors: bool,
+    pub verbose: bool,
+    pub json: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct DescriptionOutput<'a> {
+    description: &'a str,
+    tag: &'a str,
+    commit: Option<&'a str>,
+    count: Option<i32>,
+    dirty: bool,
+    version: Option<String>,
+    next_version: Option<String>,
+    head_commit: Option<&'a str>,
+    head_short_commit: Option<&'a str>,
+    remote_url: Option<&'a str>,
+}
+
+impl<'a> DescriptionOutput<'a> {
+    fn new(
+        description: &'a GitDescription,
+        head_commit: Option<&'a str>,
+        head_short_commit: Option<&'a str>,
+        remote_url: Option<&'a str>,
+    ) -> Self {
+        let version = description.tag.parse::<Version>().ok();
+        Self {
+            description: &description.description,
+            tag: &description.tag,
+            commit: description.offset.as_ref().map(|o| o.commit.as_str()),
+            count: description.offset.as_ref().map(|o| o.count),
+            dirty: description.dirty,
+            next_version: version
+                .as_ref()
+                .and_then(|v| v.next().ok())
+                .map(|v| v.to_string()),
+            version: version.map(|v| v.to_string()),
+            head_commit,
+            head_short_commit,
+            remote_url,
+        }
+    }
+}
+
+pub fn show_description(app: &App, options: ShowDescriptionOptions) -> DevtoolResult<()> {
+    let ShowDescriptionOptions {
+        dirty_tree_policy,
+        contributors,
+        verbose,
+        json,
+    } = options;
+
+    let config = app.read_config()?;
+    DirtyTreePolicy::resolve(dirty_tree_policy, config.as_ref(), DirtyTreePolicy::Ignore)
+        .check(app, true)?;
+
+    let Some(description) = app.git.describe(&DescribeOptions {
+        dirty: true,
+        ..Default::default()
+    })?
+    else {
+        if json {
+            println!("null");
         } else {
-            println!("Could not parse tag as version");
+            println!("No valid description");
+        }
+        return Ok(());
+    };
+
+    let head_commit = app.git.current_commit_hash(false)?;
+    let head_short_commit = app.git.current_commit_hash(true)?;
+    let remote_url = app.git.get_remote_url("origin")?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&DescriptionOutput::new(
+                &description,
+                head_commit.as_deref(),
+                head_short_commit.as_deref(),
+                remote_url.as_deref()
+            ))
+            .map_err(anyhow::Error::from)?
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::default();
+    table.add_row("Description", &description.description);
+    table.add_row("Tag", &description.tag);
+    table.add_row(
+        "Tag created",
+        relative_time(app.git.tag_date(&description.tag)?, Utc::now()),
+    );
+    if let Some(offset) = &description.offset {
+        table.add_row("Commits since tag", offset.count.to_string());
+        table.add_row("Commit", &offset.commit);
+    }
+    if let (Some(head_commit), Some(head_short_commit)) = (&head_commit, &head_short_commit) {
+        table.add_row("HEAD", format!("{head_commit} ({head_short_commit})"));
+    }
+    if let Some(remote_url) = &remote_url {
+        table.add_row("Remote origin", remote_url);
+    }
+    table.add_row(
+        "Working tree",
+        if description.dirty { "dirty" } else { "clean" },
+    );
+    table.add_row(
+        "Version",
+        description.tag.parse::<Version>().map_or_else(
+            |_| String::from("(could not parse tag as version)"),
+            |v| v.to_string(),
+        ),
+    );
+    table.print();
+
+    if contributors {
+        println!();
+        let mut table = Table::default();
+        for (author, count) in contributor_counts(&app.git.log_since(Some(&description.tag))?) {
+            table.add_row(author, count.to_string());
         }
-    } else {
-        println!("No valid description");
+        table.print();
+    }
+
+    if verbose {
+        println!();
+        let mut table = Table::default();
+        for (path, _) in ProjectInfo::read(app, config.as_ref(), None)?.manifests() {
+            let value = match app.git.last_version_change(&path)? {
+                Some(commit_info) => format!(
+                    "{} by {} ({})",
+                    commit_info.sha,
+                    commit_info.author,
+                    relative_time(commit_info.date, Utc::now())
+                ),
+                None => String::from("(no version change found)"),
+            };
+            table.add_row(path.display().to_string(), value);
+        }
+        table.print();
     }
 
     Ok(())
 }
+
+fn relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+
+    let seconds = (now - then).num_seconds();
+    let (count, unit) = match seconds {
+        s if s < MINUTE => return String::from("just now"),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < MONTH => (s / DAY, "day"),
+        s if s < YEAR => (s / MONTH, "month"),
+        s => (s / YEAR, "year"),
+    };
+    format!("{count} {unit}{} ago", if count == 1 { "" } else { "s" })
+}
+
+// Sorted by descending commit count and then by author name
+fn contributor_counts(log: &[(String, String)]) -> Vec<(String, usize)> {
+    let mut counts = HashMap::new();
+    for (author, _) in log {
+        *counts.entry(author.as_str()).or_insert(0) += 1;
+    }
+
+    let mut counts = counts
+        .into_iter()
+        .map(|(author, count)| (String::from(author), count))
+        .collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contributor_counts, relative_time, DescriptionOutput};
+    use anyhow::Result;
+    use chrono::{DateTime, Duration};
+    use devtool_git::GitDescription;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        "{\"description\":\"v1.2.3-4-gabcdef0-dirty\",\"tag\":\"v1.2.3\",\"commit\":\"gabcdef0\",\"count\":4,\"dirty\":true,\"version\":\"v1.2.3\",\"next_version\":\"v1.2.4\",\"head_commit\":\"abcdef0123456789abcdef0123456789abcdef01\",\"head_short_commit\":\"abcdef0\",\"remote_url\":\"git@github.com:rcook/devtool.git\"}",
+        "v1.2.3-4-gabcdef0-dirty",
+        Some("abcdef0123456789abcdef0123456789abcdef01"),
+        Some("abcdef0"),
+        Some("git@github.com:rcook/devtool.git")
+    )]
+    #[case(
+        "{\"description\":\"v1.2.3\",\"tag\":\"v1.2.3\",\"commit\":null,\"count\":null,\"dirty\":false,\"version\":\"v1.2.3\",\"next_version\":\"v1.2.4\",\"head_commit\":null,\"head_short_commit\":null,\"remote_url\":null}",
+        "v1.2.3",
+        None,
+        None,
+        None
+    )]
+    #[case(
+        "{\"description\":\"nightly\",\"tag\":\"nightly\",\"commit\":null,\"count\":null,\"dirty\":false,\"version\":null,\"next_version\":null,\"head_commit\":null,\"head_short_commit\":null,\"remote_url\":null}",
+        "nightly",
+        None,
+        None,
+        None
+    )]
+    fn description_output(
+        #[case] expected_result: &str,
+        #[case] input: &str,
+        #[case] head_commit: Option<&str>,
+        #[case] head_short_commit: Option<&str>,
+        #[case] remote_url: Option<&str>,
+    ) -> Result<()> {
+        let description = GitDescription::parse(input).expect("must parse");
+        assert_eq!(
+            expected_result,
+            serde_json::to_string(&DescriptionOutput::new(
+                &description,
+                head_commit,
+                head_short_commit,
+                remote_url
+            ))?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("just now", Duration::zero())]
+    #[case("just now", Duration::seconds(59))]
+    #[case("just now", Duration::seconds(-30))]
+    #[case("1 minute ago", Duration::seconds(90))]
+    #[case("3 hours ago", Duration::minutes(200))]
+    #[case("1 day ago", Duration::hours(24))]
+    #[case("4 days ago", Duration::days(4) + Duration::hours(5))]
+    #[case("2 months ago", Duration::days(75))]
+    #[case("3 years ago", Duration::days(3 * 365 + 10))]
+    fn relative_time_basics(#[case] expected_result: &str, #[case] age: Duration) {
+        let now = DateTime::from_timestamp(1_733_596_215, 0).expect("must be valid");
+        assert_eq!(expected_result, relative_time(now - age, now));
+    }
+
+    #[rstest]
+    #[case(&[], &[])]
+    #[case(&[("Alice", 1)], &["Alice"])]
+    #[case(&[("Bob", 2), ("Alice", 1), ("Carol", 1)], &["Carol", "Bob", "Alice", "Bob"])]
+    fn contributor_counts_basics(
+        #[case] expected_result: &[(&str, usize)],
+        #[case] authors: &[&str],
+    ) {
+        let log = authors
+            .iter()
+            .map(|a| (String::from(*a), String::from("subject")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expected_result
+                .iter()
+                .map(|(a, c)| (String::from(*a), *c))
+                .collect::<Vec<_>>(),
+            contributor_counts(&log)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use super::bump_version::match_patterns;
+use crate::app::App;
+use crate::commit_message_template::CommitMessageTemplate;
+use crate::error::DevtoolResult;
+use crate::tag_template::TagTemplate;
+use anyhow::anyhow;
+use devtool_git::DescribeOptions;
+use devtool_version::Version;
+
+pub fn undo_bump(app: &App) -> DevtoolResult<()> {
+    let config = app.read_config()?;
+    let tag_template = config
+        .as_ref()
+        .and_then(|c| c.tag_template.clone())
+        .map(TagTemplate::new)
+        .transpose()?;
+    let commit_message_template = config
+        .as_ref()
+        .and_then(|c| c.commit_message_template.clone())
+        .map(CommitMessageTemplate::new)
+        .transpose()?
+        .unwrap_or_default();
+
+    let Some(description) = app.git.describe(&DescribeOptions {
+        match_patterns: match_patterns(
+            config.as_ref().and_then(|c| c.tag_match.clone()),
+            tag_template.as_ref(),
+        ),
+        ..Default::default()
+    })?
+    else {
+        return Err(anyhow!("No tag found to undo").into());
+    };
+    let tag = description.tag;
+
+    if description.offset.is_some() {
+        return Err(anyhow!(
+            "HEAD is not at tag {tag}: only the most recent version bump can be undone"
+        )
+        .into());
+    }
+
+    // Rewriting history that others may already have fetched is not safe
+    let branch = app.git.get_current_branch()?;
+    if let Some(remote) = app.git.get_upstream_remote(&branch)? {
+        if app.git.remote_tag_exists(&remote, &tag)? {
+            return Err(
+                anyhow!("Tag {tag} was already pushed to {remote} and cannot be undone").into(),
+            );
+        }
+    }
+
+    let reset_commit = is_bump_commit(
+        &app.git.head_subject()?,
+        &tag,
+        tag_template.as_ref(),
+        &commit_message_template,
+    );
+
+    app.git.delete_tag(&tag)?;
+    println!("Deleted tag {tag}");
+
+    if reset_commit {
+        app.git.reset_keep("HEAD~1")?;
+        println!("Removed version bump commit");
+    } else {
+        println!("HEAD is not the version bump commit for tag {tag}: leaving commits unchanged");
+    }
+
+    Ok(())
+}
+
+fn is_bump_commit(
+    subject: &str,
+    tag: &str,
+    tag_template: Option<&TagTemplate>,
+    commit_message_template: &CommitMessageTemplate,
+) -> bool {
+    let version = match tag_template {
+        Some(t) => t.parse_version(tag).ok(),
+        None => tag.parse::<Version>().ok(),
+    };
+    version.is_some_and(|mut v| {
+        v.set_prefix(false);
+        subject == commit_message_template.format(&v.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_bump_commit;
+    use crate::commit_message_template::CommitMessageTemplate;
+    use crate::tag_template::TagTemplate;
+    use anyhow::Result;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(true, "Bump version to 1.2.3", "v1.2.3", None, None)]
+    #[case(true, "Bump version to 1.2.3", "1.2.3", None, None)]
+    #[case(
+        true,
+        "Bump version to 1.2.3",
+        "release/1.2.3",
+        Some("release/{version}"),
+        None
+    )]
+    #[case(false, "Bump version to 1.2.2", "v1.2.3", None, None)]
+    #[case(
+        true,
+        "chore(release): 1.2.3",
+        "v1.2.3",
+        None,
+        Some("chore(release): {version}")
+    )]
+    #[case(
+        false,
+        "Bump version to 1.2.3",
+        "v1.2.3",
+        None,
+        Some("chore(release): {version}")
+    )]
+    #[case(false, "Fix typo", "v1.2.3", None, None)]
+    #[case(false, "Bump version to 1.2.3", "nightly", None, None)]
+    fn is_bump_commit_basics(
+        #[case] expected_result: bool,
+        #[case] subject: &str,
+        #[case] tag: &str,
+        #[case] tag_template: Option<&str>,
+        #[case] commit_message_template: Option<&str>,
+    ) -> Result<()> {
+        let tag_template = tag_template.map(TagTemplate::new).transpose()?;
+        let commit_message_template = commit_message_template
+            .map(CommitMessageTemplate::new)
+            .transpose()?
+            .unwrap_or_default();
+        assert_eq!(
+            expected_result,
+            is_bump_commit(
+                subject,
+                tag,
+                tag_template.as_ref(),
+                &commit_message_template
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use anyhow::{bail, Result};
+
+const PLACEHOLDER: &str = "{version}";
+const DEFAULT_TEMPLATE: &str = "Bump version to {version}";
+
+#[derive(Debug)]
+pub struct CommitMessageTemplate {
+    template: String,
+}
+
+impl CommitMessageTemplate {
+    pub fn new<S>(template: S) -> Result<Self>
+    where
+        S: Into<String>,
+    {
+        let template = template.into();
+        if !template.contains(PLACEHOLDER) {
+            bail!("Commit message template \"{template}\" must contain {PLACEHOLDER} placeholder")
+        }
+
+        Ok(Self { template })
+    }
+
+    pub fn format(&self, version: &str) -> String {
+        self.template.replace(PLACEHOLDER, version)
+    }
+}
+
+impl Default for CommitMessageTemplate {
+    fn default() -> Self {
+        Self {
+            template: String::from(DEFAULT_TEMPLATE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommitMessageTemplate;
+    use anyhow::Result;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("chore(release): 1.2.3", "chore(release): {version}", "1.2.3")]
+    #[case("1.2.3: release 1.2.3", "{version}: release {version}", "1.2.3")]
+    fn format(
+        #[case] expected_result: &str,
+        #[case] template: &str,
+        #[case] version: &str,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result,
+            CommitMessageTemplate::new(template)?.format(version)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn format_default() {
+        assert_eq!(
+            "Bump version to 1.2.3",
+            CommitMessageTemplate::default().format("1.2.3")
+        );
+    }
+
+    #[rstest]
+    #[case("Release")]
+    #[case("Release {major}")]
+    #[case("")]
+    fn new_missing_placeholder(#[case] template: &str) {
+        assert!(CommitMessageTemplate::new(template).is_err());
+    }
+}
@@ -19,4 +19,18 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
+pub const CHANGELOG_FILE_NAME: &str = "CHANGELOG.md";
+
 pub const CONFIG_FILE_NAME: &str = ".devtool.yaml";
+
+pub const GLOBAL_CONFIG_FILE_NAME: &str = "config.yaml";
+
+pub const GIT_IGNORE_FILE_NAME: &str = ".gitignore";
+
+pub const IGNORE_FILE_NAME: &str = ".devtoolignore";
+
+pub const RELEASE_STATE_FILE_NAME: &str = ".devtool-release-state.json";
+
+pub const GITHUB_TOKEN_ENV_NAME: &str = "GITHUB_TOKEN";
+
+pub const VERSION_ENV_NAME: &str = "DEVTOOL_VERSION";
@@ -0,0 +1,125 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::app::App;
+use crate::error::DevtoolError;
+use crate::reporter::Reporter;
+use crate::serialization::Config;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DirtyTreePolicy {
+    #[value(help = "Fail if the working directory is not clean")]
+    Error,
+
+    #[value(help = "Print a warning if the working directory is not clean")]
+    Warn,
+
+    #[value(help = "Do not check the working directory")]
+    Ignore,
+}
+
+impl DirtyTreePolicy {
+    pub fn resolve(value: Option<Self>, config: Option<&Config>, default: Self) -> Self {
+        value
+            .or_else(|| config.and_then(|c| c.dirty_tree_policy))
+            .unwrap_or(default)
+    }
+
+    pub fn check(self, app: &App, include_untracked: bool) -> Result<()> {
+        if self == Self::Ignore {
+            return Ok(());
+        }
+
+        self.apply(&app.reporter, !app.git.is_clean(include_untracked)?)
+    }
+
+    fn apply(self, reporter: &Reporter, is_dirty: bool) -> Result<()> {
+        if !is_dirty {
+            return Ok(());
+        }
+
+        match self {
+            Self::Error => Err(DevtoolError::DirtyWorkingTree(anyhow!(
+                "Git working directory is not clean: please revert or commit pending changes and try again"
+            ))
+            .into()),
+            Self::Warn => reporter.warn("Git working directory is not clean"),
+            Self::Ignore => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DirtyTreePolicy;
+    use crate::reporter::Reporter;
+    use crate::serialization::Config;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(true, DirtyTreePolicy::Error, false, false)]
+    #[case(false, DirtyTreePolicy::Error, true, false)]
+    #[case(true, DirtyTreePolicy::Warn, false, false)]
+    #[case(true, DirtyTreePolicy::Warn, true, false)]
+    #[case(false, DirtyTreePolicy::Warn, true, true)]
+    #[case(true, DirtyTreePolicy::Ignore, false, false)]
+    #[case(true, DirtyTreePolicy::Ignore, true, false)]
+    #[case(true, DirtyTreePolicy::Ignore, true, true)]
+    fn apply(
+        #[case] expected_ok: bool,
+        #[case] policy: DirtyTreePolicy,
+        #[case] is_dirty: bool,
+        #[case] strict: bool,
+    ) {
+        assert_eq!(
+            expected_ok,
+            policy.apply(&Reporter { strict }, is_dirty).is_ok()
+        );
+    }
+
+    #[rstest]
+    #[case(
+        DirtyTreePolicy::Warn,
+        Some(DirtyTreePolicy::Warn),
+        Some(DirtyTreePolicy::Ignore)
+    )]
+    #[case(DirtyTreePolicy::Warn, Some(DirtyTreePolicy::Warn), None)]
+    #[case(DirtyTreePolicy::Ignore, None, Some(DirtyTreePolicy::Ignore))]
+    #[case(DirtyTreePolicy::Error, None, None)]
+    fn resolve(
+        #[case] expected_result: DirtyTreePolicy,
+        #[case] value: Option<DirtyTreePolicy>,
+        #[case] config_value: Option<DirtyTreePolicy>,
+    ) {
+        let config = Config {
+            dirty_tree_policy: config_value,
+            ..Default::default()
+        };
+        assert_eq!(
+            expected_result,
+            DirtyTreePolicy::resolve(value, Some(&config), DirtyTreePolicy::Error)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use devtool_git::GitError;
+use devtool_version::VersionParseError;
+use std::error::Error as StdError;
+use std::io::Error as IOError;
+use std::result::Result as StdResult;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DevtoolError {
+    #[error(transparent)]
+    Git(#[from] GitError),
+
+    #[error(transparent)]
+    VersionParse(#[from] VersionParseError),
+
+    #[error(transparent)]
+    Config(anyhow::Error),
+
+    #[error(transparent)]
+    NoGitRepository(anyhow::Error),
+
+    #[error(transparent)]
+    DirtyWorkingTree(anyhow::Error),
+
+    #[error(transparent)]
+    NoUpstream(anyhow::Error),
+
+    #[error(transparent)]
+    Io(#[from] IOError),
+
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+pub type DevtoolResult<T> = StdResult<T, DevtoolError>;
+
+impl DevtoolError {
+    // Distinct exit codes let scripts handle common failures without
+    // matching on error messages
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::NoGitRepository(_) => 2,
+            Self::Git(e) => git_exit_code(e),
+            Self::DirtyWorkingTree(_) => 3,
+            Self::NoUpstream(_) => 4,
+            Self::VersionParse(_) | Self::Config(_) | Self::Io(_) => 1,
+            // Typed errors wrapped in context are only found by walking the chain
+            Self::Other(e) => e
+                .chain()
+                .find_map(|c| {
+                    c.downcast_ref::<Self>()
+                        .map(Self::exit_code)
+                        .or_else(|| c.downcast_ref::<GitError>().map(git_exit_code))
+                })
+                .unwrap_or(1),
+        }
+    }
+}
+
+impl From<anyhow::Error> for DevtoolError {
+    fn from(value: anyhow::Error) -> Self {
+        let value = match unwrap_bare::<Self>(value) {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let value = match unwrap_bare::<GitError>(value) {
+            Ok(e) => return Self::Git(e),
+            Err(e) => e,
+        };
+        let value = match unwrap_bare::<VersionParseError>(value) {
+            Ok(e) => return Self::VersionParse(e),
+            Err(e) => e,
+        };
+        let value = match unwrap_bare::<IOError>(value) {
+            Ok(e) => return Self::Io(e),
+            Err(e) => e,
+        };
+
+        // Keep the whole error so that its context is still displayed
+        match value.chain().find_map(|c| c.downcast_ref::<Self>()) {
+            Some(Self::Config(_)) => Self::Config(value),
+            Some(Self::NoGitRepository(_)) => Self::NoGitRepository(value),
+            Some(Self::DirtyWorkingTree(_)) => Self::DirtyWorkingTree(value),
+            Some(Self::NoUpstream(_)) => Self::NoUpstream(value),
+            _ => Self::Other(value),
+        }
+    }
+}
+
+fn git_exit_code(e: &GitError) -> i32 {
+    match e {
+        GitError::CommandFailedWithCode(_, _, stderr)
+            if stderr.contains("not a git repository") =>
+        {
+            2
+        }
+        _ => 1,
+    }
+}
+
+// Downcasting by value drops any context so only an error without context is unwrapped
+fn unwrap_bare<E>(value: anyhow::Error) -> StdResult<E, anyhow::Error>
+where
+    E: StdError + Send + Sync + 'static,
+{
+    if value.chain().next().is_some_and(<dyn StdError>::is::<E>) {
+        value.downcast::<E>()
+    } else {
+        Err(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DevtoolError;
+    use anyhow::anyhow;
+    use devtool_git::GitError;
+    use rstest::rstest;
+
+    #[test]
+    fn git_error() {
+        let e = DevtoolError::from(anyhow::Error::from(GitError::EmailOrNameNotConfigured));
+        assert!(matches!(
+            e,
+            DevtoolError::Git(GitError::EmailOrNameNotConfigured)
+        ));
+        assert_eq!("e-mail or name is not configured in Git", e.to_string());
+    }
+
+    #[test]
+    fn config_error() {
+        let e = DevtoolError::from(anyhow::Error::from(DevtoolError::Config(anyhow!(
+            "bad config"
+        ))));
+        assert!(matches!(e, DevtoolError::Config(_)));
+        assert_eq!("bad config", e.to_string());
+    }
+
+    #[rstest]
+    #[case(1, DevtoolError::Other(anyhow!("something went wrong")))]
+    #[case(1, DevtoolError::Config(anyhow!("bad config")))]
+    #[case(1, DevtoolError::Git(GitError::EmailOrNameNotConfigured))]
+    #[case(2, DevtoolError::NoGitRepository(anyhow!("no repo")))]
+    #[case(
+        2,
+        DevtoolError::Git(GitError::CommandFailedWithCode(
+            String::from("status"),
+            128,
+            String::from("fatal: not a git repository (or any of the parent directories): .git")
+        ))
+    )]
+    #[case(
+        1,
+        DevtoolError::Git(GitError::CommandFailedWithCode(
+            String::from("push"),
+            128,
+            String::from("fatal: unable to access remote")
+        ))
+    )]
+    #[case(3, DevtoolError::DirtyWorkingTree(anyhow!("dirty")))]
+    #[case(4, DevtoolError::NoUpstream(anyhow!("no upstream")))]
+    fn exit_code(#[case] expected_result: i32, #[case] e: DevtoolError) {
+        assert_eq!(expected_result, e.exit_code());
+    }
+
+    #[test]
+    fn exit_code_with_context() {
+        let e = DevtoolError::from(
+            anyhow::Error::from(DevtoolError::DirtyWorkingTree(anyhow!("dirty")))
+                .context("Release did not complete"),
+        );
+        assert!(matches!(e, DevtoolError::DirtyWorkingTree(_)));
+        assert_eq!(3, e.exit_code());
+    }
+
+    #[test]
+    fn git_error_with_context() {
+        let e = DevtoolError::from(
+            anyhow::Error::from(GitError::CommandFailedWithCode(
+                String::from("status"),
+                128,
+                String::from(
+                    "fatal: not a git repository (or any of the parent directories): .git",
+                ),
+            ))
+            .context("Release did not complete: run devtool resume to continue"),
+        );
+        assert_eq!(
+            "Release did not complete: run devtool resume to continue",
+            e.to_string()
+        );
+        assert_eq!(2, e.exit_code());
+    }
+
+    #[test]
+    fn other_error() {
+        let e = DevtoolError::from(anyhow!("something went wrong"));
+        assert!(matches!(e, DevtoolError::Other(_)));
+        assert_eq!("something went wrong", e.to_string());
+    }
+}
@@ -19,14 +19,16 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
-use chrono::{DateTime, Utc};
+use super::time_format::TimeFormat;
+use chrono::Utc;
 use log::{Level, Record};
 use serde::{Serialize, Serializer};
+use serde_json::Value;
 
 #[derive(Serialize)]
 pub struct BriefEntry {
-    #[serde(rename = "ts", serialize_with = "serialize_date_time")]
-    time: DateTime<Utc>,
+    #[serde(rename = "ts")]
+    time: Value,
     #[serde(rename = "tgt")]
     target: String,
     #[serde(rename = "l", serialize_with = "serialize_level")]
@@ -36,9 +38,9 @@ pub struct BriefEntry {
 }
 
 impl BriefEntry {
-    pub fn new(record: &Record) -> Self {
+    pub fn new(record: &Record, time_format: &TimeFormat) -> Self {
         Self {
-            time: Utc::now(),
+            time: time_format.format(&Utc::now()),
             target: String::from(record.target()),
             level: record.level(),
             message: record.args().to_string(),
@@ -48,8 +50,8 @@ impl BriefEntry {
 
 #[derive(Serialize)]
 pub struct DetailedEntry {
-    #[serde(rename = "ts", serialize_with = "serialize_date_time")]
-    time: DateTime<Utc>,
+    #[serde(rename = "ts")]
+    time: Value,
     #[serde(rename = "tgt")]
     target: String,
     #[serde(rename = "lev", serialize_with = "serialize_level")]
@@ -63,9 +65,9 @@ pub struct DetailedEntry {
 }
 
 impl DetailedEntry {
-    pub fn new(record: &Record) -> Self {
+    pub fn new(record: &Record, time_format: &TimeFormat) -> Self {
         Self {
-            time: Utc::now(),
+            time: time_format.format(&Utc::now()),
             target: String::from(record.target()),
             level: record.level(),
             message: record.args().to_string(),
@@ -75,13 +77,6 @@ impl DetailedEntry {
     }
 }
 
-fn serialize_date_time<S>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_str(&value.to_rfc3339())
-}
-
 #[allow(clippy::trivially_copy_pass_by_ref)]
 fn serialize_level<S>(value: &Level, serializer: S) -> Result<S::Ok, S::Error>
 where
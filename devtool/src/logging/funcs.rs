@@ -1,37 +1,35 @@
-// Copyright (c) 2023 Richard Cook
-//
-// Permission is hereby granted, free of charge, to any person obtaining
-// a copy of this software and associated documentation files (the
-// "Software"), to deal in the Software without restriction, including
-// without limitation the rights to use, copy, modify, merge, publish,
-// distribute, sublicense, and/or sell copies of the Software, and to
-// permit persons to whom the Software is furnished to do so, subject to
-// the following conditions:
-//
-// The above copyright notice and this permission notice shall be
-// included in all copies or substantial portions of the Software.
-//
-// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
-// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
-// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
-// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
-// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
-// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
-// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
-//
-use super::logger::{BriefLogger, DetailedLogger};
+use super::log_output::LogOutput;
+use super::logger::{ArrayLogger, BriefLogger, ConsoleLogger, DetailedLogger};
+use super::logging_options::LoggingOptions;
+use crate::args::LogFormat;
 use anyhow::Result;
-use log::LevelFilter;
+use log::Log;
 
-static BRIEF_LOGGER: BriefLogger = BriefLogger;
-static DETAILED_LOGGER: DetailedLogger = DetailedLogger;
+pub fn init_logging(options: LoggingOptions) -> Result<()> {
+    let LoggingOptions {
+        detailed,
+        level_filter,
+        log_format,
+        time_format,
+        log_file,
+    } = options;
 
-pub fn init_logging(detailed: bool, level_filter: LevelFilter) -> Result<()> {
-    log::set_logger(if detailed {
-        &DETAILED_LOGGER
-    } else {
-        &BRIEF_LOGGER
-    })?;
+    let output = LogOutput::new(log_file.as_deref())?;
+    let logger: Box<dyn Log> = match (log_format, detailed) {
+        (LogFormat::Console, detailed) => Box::new(ConsoleLogger { detailed, output }),
+        (LogFormat::Json, false) => Box::new(BriefLogger {
+            time_format,
+            output,
+        }),
+        (LogFormat::Json, true) => Box::new(DetailedLogger {
+            time_format,
+            output,
+        }),
+        (LogFormat::JsonArray, detailed) => {
+            Box::new(ArrayLogger::new(detailed, time_format, output))
+        }
+    };
+    log::set_boxed_logger(logger)?;
     log::set_max_level(level_filter);
     Ok(())
 }
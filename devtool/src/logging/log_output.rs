@@ -0,0 +1,91 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Result as IOResult, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+pub struct LogOutput {
+    file: Option<Mutex<LineWriter<File>>>,
+}
+
+impl LogOutput {
+    pub fn new(log_file: Option<&Path>) -> IOResult<Self> {
+        let file = match log_file {
+            Some(path) => Some(Mutex::new(LineWriter::new(
+                OpenOptions::new().create(true).append(true).open(path)?,
+            ))),
+            None => None,
+        };
+        Ok(Self { file })
+    }
+
+    pub fn write_line(&self, s: &str) {
+        println!("{s}");
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                _ = writeln!(file, "{s}");
+            }
+        }
+    }
+
+    pub fn flush(&self) {
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                _ = file.flush();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LogOutput;
+    use anyhow::Result;
+    use std::env::temp_dir;
+    use std::fs::{read_to_string, remove_file, write};
+    use std::process;
+
+    #[test]
+    fn write_line_appends_to_file() -> Result<()> {
+        let path = temp_dir().join(format!("devtool-log-output-{}.log", process::id()));
+        write(&path, "existing\n")?;
+
+        let output = LogOutput::new(Some(&path))?;
+        output.write_line("first");
+        output.write_line("second");
+        drop(output);
+
+        let result = read_to_string(&path);
+        remove_file(&path)?;
+        assert_eq!("existing\nfirst\nsecond\n", result?);
+        Ok(())
+    }
+
+    #[test]
+    fn new_fails_for_missing_dir() {
+        let path = temp_dir()
+            .join(format!("devtool-log-output-missing-{}", process::id()))
+            .join("devtool.log");
+        assert!(LogOutput::new(Some(&path)).is_err());
+    }
+}
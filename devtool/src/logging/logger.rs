@@ -20,42 +20,223 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 use super::entry::{BriefEntry, DetailedEntry};
-use log::{Log, Metadata, Record};
+use super::log_output::LogOutput;
+use super::time_format::TimeFormat;
+use colored::Colorize;
+use log::{Level, Log, Metadata, Record};
+use serde_json::Value;
+use std::fmt::Write;
+use std::mem::take;
+use std::sync::Mutex;
 
-pub struct BriefLogger;
+pub struct ConsoleLogger {
+    pub detailed: bool,
+    pub output: LogOutput,
+}
+
+impl ConsoleLogger {
+    fn format_entry(&self, record: &Record) -> String {
+        let level = format!("{:<5}", record.level());
+        let level = match record.level() {
+            Level::Error => level.red(),
+            Level::Warn => level.yellow(),
+            Level::Info => level.green(),
+            Level::Debug => level.blue(),
+            Level::Trace => level.dimmed(),
+        };
+        let mut s = format!("{level} {}: {}", record.target().dimmed(), record.args());
+        if self.detailed {
+            if let (Some(file), Some(line)) = (record.file(), record.line()) {
+                _ = write!(s, " {}", format!("({file}:{line})").dimmed());
+            }
+        }
+        s
+    }
+}
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn flush(&self) {
+        self.output.flush();
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.output.write_line(&self.format_entry(record));
+        }
+    }
+}
+
+pub struct BriefLogger {
+    pub time_format: TimeFormat,
+    pub output: LogOutput,
+}
 
 impl Log for BriefLogger {
     fn enabled(&self, _metadata: &Metadata) -> bool {
         true
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        self.output.flush();
+    }
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
-            match serde_json::to_string(&BriefEntry::new(record)) {
-                Ok(s) => println!("{s}"),
-                Err(_) => println!("{{\"msg\": \"serialization-failed\"}}"),
+            match serde_json::to_string(&BriefEntry::new(record, &self.time_format)) {
+                Ok(s) => self.output.write_line(&s),
+                Err(_) => self
+                    .output
+                    .write_line("{\"msg\": \"serialization-failed\"}"),
             }
         }
     }
 }
 
-pub struct DetailedLogger;
+pub struct DetailedLogger {
+    pub time_format: TimeFormat,
+    pub output: LogOutput,
+}
 
 impl Log for DetailedLogger {
     fn enabled(&self, _metadata: &Metadata) -> bool {
         true
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        self.output.flush();
+    }
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
-            match serde_json::to_string(&DetailedEntry::new(record)) {
-                Ok(s) => println!("{s}"),
-                Err(_) => println!("{{\"msg\": \"serialization-failed\"}}"),
+            match serde_json::to_string(&DetailedEntry::new(record, &self.time_format)) {
+                Ok(s) => self.output.write_line(&s),
+                Err(_) => self
+                    .output
+                    .write_line("{\"msg\": \"serialization-failed\"}"),
             }
         }
     }
 }
+
+pub struct ArrayLogger {
+    detailed: bool,
+    time_format: TimeFormat,
+    output: LogOutput,
+    entries: Mutex<Vec<Value>>,
+}
+
+impl ArrayLogger {
+    pub const fn new(detailed: bool, time_format: TimeFormat, output: LogOutput) -> Self {
+        Self {
+            detailed,
+            time_format,
+            output,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn take_entries(&self) -> Option<String> {
+        let entries = take(&mut *self.entries.lock().ok()?);
+
+        if entries.is_empty() {
+            return None;
+        }
+
+        Some(
+            serde_json::to_string(&entries)
+                .unwrap_or_else(|_| String::from("[{\"msg\": \"serialization-failed\"}]")),
+        )
+    }
+}
+
+impl Log for ArrayLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn flush(&self) {
+        if let Some(s) = self.take_entries() {
+            self.output.write_line(&s);
+        }
+        self.output.flush();
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let value = if self.detailed {
+                serde_json::to_value(DetailedEntry::new(record, &self.time_format))
+            } else {
+                serde_json::to_value(BriefEntry::new(record, &self.time_format))
+            }
+            .unwrap_or_else(|_| serde_json::json!({ "msg": "serialization-failed" }));
+
+            if let Ok(mut entries) = self.entries.lock() {
+                entries.push(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrayLogger, ConsoleLogger};
+    use crate::logging::log_output::LogOutput;
+    use crate::logging::TimeFormat;
+    use crate::test_colour::strip_colour;
+    use anyhow::Result;
+    use log::{Level, Log, Record};
+    use rstest::rstest;
+    use serde_json::Value;
+
+    #[rstest]
+    #[case("WARN  devtool::run: tag has drifted", false)]
+    #[case("WARN  devtool::run: tag has drifted (src/run.rs:42)", true)]
+    fn console_logger(#[case] expected_result: &str, #[case] detailed: bool) -> Result<()> {
+        let logger = ConsoleLogger {
+            detailed,
+            output: LogOutput::new(None)?,
+        };
+        let result = logger.format_entry(
+            &Record::builder()
+                .args(format_args!("tag has drifted"))
+                .level(Level::Warn)
+                .target("devtool::run")
+                .file(Some("src/run.rs"))
+                .line(Some(42))
+                .build(),
+        );
+        assert_eq!(expected_result, strip_colour(&result));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(false, "l")]
+    #[case(true, "lev")]
+    fn array_logger(#[case] detailed: bool, #[case] level_key: &str) -> Result<()> {
+        let logger = ArrayLogger::new(detailed, TimeFormat::EpochMs, LogOutput::new(None)?);
+        for message in ["first", "second"] {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(Level::Warn)
+                    .target("test")
+                    .build(),
+            );
+        }
+
+        let value = serde_json::from_str::<Value>(&logger.take_entries().expect("must exist"))?;
+        let entries = value.as_array().expect("must be array");
+        assert_eq!(2, entries.len());
+        assert_eq!("first", entries[0]["msg"]);
+        assert_eq!("second", entries[1]["msg"]);
+        assert_eq!("WARN", entries[0][level_key]);
+        assert!(entries[0]["ts"].is_i64());
+
+        assert!(logger.take_entries().is_none());
+        Ok(())
+    }
+}
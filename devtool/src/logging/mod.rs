@@ -21,6 +21,11 @@
 //
 mod entry;
 mod funcs;
+mod log_output;
 mod logger;
+mod logging_options;
+mod time_format;
 
 pub use self::funcs::init_logging;
+pub use self::logging_options::LoggingOptions;
+pub use self::time_format::TimeFormat;
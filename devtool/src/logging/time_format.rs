@@ -0,0 +1,99 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use anyhow::{anyhow, Error};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum TimeFormat {
+    #[default]
+    Rfc3339,
+    EpochMs,
+    Custom(String),
+}
+
+impl TimeFormat {
+    pub fn format(&self, time: &DateTime<Utc>) -> Value {
+        match self {
+            Self::Rfc3339 => Value::from(time.to_rfc3339()),
+            Self::EpochMs => Value::from(time.timestamp_millis()),
+            Self::Custom(format) => Value::from(time.format(format).to_string()),
+        }
+    }
+}
+
+impl FromStr for TimeFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rfc3339" => Ok(Self::Rfc3339),
+            "epoch-ms" => Ok(Self::EpochMs),
+            _ => {
+                let Some(format) = s.strip_prefix("custom:") else {
+                    return Err(anyhow!(
+                        "Invalid time format \"{s}\": expected rfc3339, epoch-ms or custom:FMT"
+                    ));
+                };
+
+                // Invalid specifiers would otherwise cause a panic at formatting time
+                if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                    return Err(anyhow!("Invalid strftime format \"{format}\""));
+                }
+
+                Ok(Self::Custom(String::from(format)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeFormat;
+    use anyhow::Result;
+    use chrono::DateTime;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    #[rstest]
+    #[case(json!("2024-12-07T18:30:15.123+00:00"), "rfc3339")]
+    #[case(json!(1_733_596_215_123_i64), "epoch-ms")]
+    #[case(json!("2024-12-07 18:30:15"), "custom:%Y-%m-%d %H:%M:%S")]
+    #[case(json!("1733596215"), "custom:%s")]
+    fn format(#[case] expected_result: Value, #[case] input: &str) -> Result<()> {
+        let time = DateTime::from_timestamp_millis(1_733_596_215_123).expect("must be valid");
+        assert_eq!(expected_result, input.parse::<TimeFormat>()?.format(&time));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("RFC3339")]
+    #[case("epoch")]
+    #[case("custom:%Y-%")]
+    #[case("custom:%Q")]
+    fn from_str_invalid(#[case] input: &str) {
+        assert!(input.parse::<TimeFormat>().is_err());
+    }
+}
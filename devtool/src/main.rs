@@ -27,6 +27,7 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::derive_partial_eq_without_eq)]
 #![allow(clippy::enum_glob_use)]
+#![allow(clippy::literal_string_with_formatting_args)]
 #![allow(clippy::match_wildcard_for_single_variants)]
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::module_name_repetitions)]
@@ -34,23 +35,41 @@
 #![allow(clippy::option_if_let_else)]
 mod app;
 mod args;
+mod changelog;
 mod commands;
+mod commit_message_template;
 mod constants;
+mod dirty_tree_policy;
+mod error;
+mod github;
 mod logging;
+mod manifests;
+mod paths;
 mod project_info;
+mod release_state;
+mod reporter;
 mod run;
 mod serialization;
+mod table;
+mod tag_template;
+#[cfg(test)]
+mod test_colour;
+#[cfg(test)]
+mod test_dir;
+mod text_file;
 
 use crate::run::run;
 use colored::Colorize;
 use std::process::exit;
 
 fn main() {
-    exit(match run() {
+    let result = run();
+    log::logger().flush();
+    exit(match result {
         Ok(()) => 0,
         Err(e) => {
             println!("{}", format!("{e}").bright_red());
-            1
+            e.exit_code()
         }
     })
 }
@@ -0,0 +1,1255 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::text_file::{read_toml_file_edit, strip_bom};
+use anyhow::{anyhow, bail, Result};
+use devtool_version::Version;
+use joatmon::safe_write_file;
+use serde_json::Value as JsonValue;
+use std::ffi::OsStr;
+use std::fs::read_to_string;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, DocumentMut, Item, Table, TableLike, Value};
+
+pub trait ManifestUpdater {
+    fn package_name(&self, path: &Path) -> Result<Option<String>>;
+    fn read_version(&self, path: &Path) -> Result<Option<Version>>;
+    fn plan_version(
+        &self,
+        path: &Path,
+        version: &Version,
+    ) -> Result<Vec<(PathBuf, ManifestUpdate)>>;
+    fn version_paths(&self, path: &Path) -> Result<Vec<PathBuf>>;
+
+    fn update_version(&self, path: &Path, version: &Version) -> Result<bool> {
+        let mut changed = false;
+        for (path, update) in self.plan_version(path, version)? {
+            changed |= update.write(&path)?;
+        }
+        Ok(changed)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ManifestKind {
+    Cargo,
+    Pyproject,
+    Gradle,
+    PackageJson,
+    SetupCfg,
+}
+
+impl ManifestKind {
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            "Cargo.toml" => Some(Self::Cargo),
+            "pyproject.toml" => Some(Self::Pyproject),
+            "gradle.properties" | "build.gradle" | "build.gradle.kts" => Some(Self::Gradle),
+            "package.json" => Some(Self::PackageJson),
+            "setup.cfg" => Some(Self::SetupCfg),
+            _ => None,
+        }
+    }
+
+    // Generic version keys can only be applied to TOML manifests
+    pub const fn is_toml(self) -> bool {
+        matches!(self, Self::Cargo | Self::Pyproject)
+    }
+
+    fn updater(self) -> &'static dyn ManifestUpdater {
+        match self {
+            Self::Cargo => &CargoToml,
+            Self::Pyproject => &PyprojectToml,
+            Self::Gradle => &Gradle,
+            Self::PackageJson => &PackageJson,
+            Self::SetupCfg => &SetupCfg,
+        }
+    }
+}
+
+impl ManifestUpdater for ManifestKind {
+    fn package_name(&self, path: &Path) -> Result<Option<String>> {
+        self.updater().package_name(path)
+    }
+
+    fn read_version(&self, path: &Path) -> Result<Option<Version>> {
+        self.updater().read_version(path)
+    }
+
+    fn plan_version(
+        &self,
+        path: &Path,
+        version: &Version,
+    ) -> Result<Vec<(PathBuf, ManifestUpdate)>> {
+        self.updater().plan_version(path, version)
+    }
+
+    fn version_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        self.updater().version_paths(path)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ManifestUpdate {
+    Changed(String),
+    UpToDate,
+    NotApplicable,
+}
+
+impl ManifestUpdate {
+    pub fn write(self, path: &Path) -> Result<bool> {
+        match self {
+            Self::Changed(s) => {
+                safe_write_file(path, s, true)?;
+                Ok(true)
+            }
+            Self::UpToDate => {
+                println!("{} is already up to date", path.display());
+                Ok(false)
+            }
+            Self::NotApplicable => Ok(false),
+        }
+    }
+
+    fn from_texts(original: &str, updated: String) -> Self {
+        if updated == original {
+            Self::UpToDate
+        } else {
+            Self::Changed(updated)
+        }
+    }
+}
+
+struct CargoToml;
+
+impl ManifestUpdater for CargoToml {
+    fn package_name(&self, path: &Path) -> Result<Option<String>> {
+        Ok(read_toml_file_edit(path)?
+            .get("package")
+            .and_then(|i| i.get("name"))
+            .and_then(Item::as_str)
+            .map(String::from))
+    }
+
+    // Members inheriting the version from the workspace have no version of their own
+    fn read_version(&self, path: &Path) -> Result<Option<Version>> {
+        let doc = read_toml_file_edit(path)?;
+        Ok(doc
+            .get("package")
+            .and_then(|i| i.get("version"))
+            .and_then(Item::as_str)
+            .or_else(|| {
+                doc.get("workspace")
+                    .and_then(|i| i.get("package"))
+                    .and_then(|i| i.get("version"))
+                    .and_then(Item::as_str)
+            })
+            .and_then(parse_version))
+    }
+
+    fn plan_version(
+        &self,
+        path: &Path,
+        version: &Version,
+    ) -> Result<Vec<(PathBuf, ManifestUpdate)>> {
+        Ok(vec![(
+            path.to_path_buf(),
+            set_cargo_version(&read_to_string(path)?, version)?,
+        )])
+    }
+
+    fn version_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        Ok(vec![path.to_path_buf()])
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PyprojectVersion {
+    Static,
+    Files(Vec<PathBuf>),
+}
+
+struct PyprojectToml;
+
+impl PyprojectToml {
+    fn resolve(path: &Path, version_paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        version_paths.into_iter().map(|p| dir.join(p)).collect()
+    }
+}
+
+impl ManifestUpdater for PyprojectToml {
+    fn package_name(&self, path: &Path) -> Result<Option<String>> {
+        Ok(read_toml_file_edit(path)?
+            .get("project")
+            .and_then(|i| i.get("name"))
+            .and_then(Item::as_str)
+            .map(String::from))
+    }
+
+    fn read_version(&self, path: &Path) -> Result<Option<Version>> {
+        let doc = read_toml_file_edit(path)?;
+        Ok(match pyproject_version(&doc)? {
+            Some(PyprojectVersion::Static) => doc
+                .get("project")
+                .and_then(|i| i.get("version"))
+                .and_then(Item::as_str)
+                .and_then(parse_version),
+            Some(PyprojectVersion::Files(version_paths)) => {
+                match Self::resolve(path, version_paths).first() {
+                    Some(version_path) if version_path.is_file() => {
+                        parse_version(&read_to_string(version_path)?)
+                    }
+                    _ => None,
+                }
+            }
+            None => None,
+        })
+    }
+
+    fn plan_version(
+        &self,
+        path: &Path,
+        version: &Version,
+    ) -> Result<Vec<(PathBuf, ManifestUpdate)>> {
+        let original = read_to_string(path)?;
+        match pyproject_version(&strip_bom(&original).parse::<DocumentMut>()?)? {
+            Some(PyprojectVersion::Static) => Ok(vec![(
+                path.to_path_buf(),
+                set_toml_version(&original, "project", version)?,
+            )]),
+            Some(PyprojectVersion::Files(version_paths)) => Self::resolve(path, version_paths)
+                .into_iter()
+                .map(|version_path| {
+                    let original = if version_path.is_file() {
+                        read_to_string(&version_path)?
+                    } else {
+                        String::new()
+                    };
+                    let update = set_version_file(&original, version);
+                    Ok((version_path, update))
+                })
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn version_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        Ok(match pyproject_version(&read_toml_file_edit(path)?)? {
+            Some(PyprojectVersion::Files(version_paths)) => Self::resolve(path, version_paths),
+            _ => vec![path.to_path_buf()],
+        })
+    }
+}
+
+struct Gradle;
+
+impl Gradle {
+    fn value_range(path: &Path) -> fn(&str) -> Option<Range<usize>> {
+        if path.file_name() == Some(OsStr::new("gradle.properties")) {
+            properties_version_range
+        } else {
+            gradle_build_version_range
+        }
+    }
+}
+
+impl ManifestUpdater for Gradle {
+    // Gradle project names live in settings.gradle rather than alongside the version
+    fn package_name(&self, _path: &Path) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn read_version(&self, path: &Path) -> Result<Option<Version>> {
+        let value_range = Self::value_range(path);
+        Ok(read_to_string(path)?
+            .split_inclusive('\n')
+            .find_map(|line| value_range(line).map(|r| String::from(&line[r])))
+            .as_deref()
+            .and_then(parse_version))
+    }
+
+    fn plan_version(
+        &self,
+        path: &Path,
+        version: &Version,
+    ) -> Result<Vec<(PathBuf, ManifestUpdate)>> {
+        Ok(vec![(
+            path.to_path_buf(),
+            set_line_version(&read_to_string(path)?, version, Self::value_range(path)),
+        )])
+    }
+
+    fn version_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        Ok(vec![path.to_path_buf()])
+    }
+}
+
+struct PackageJson;
+
+impl ManifestUpdater for PackageJson {
+    fn package_name(&self, path: &Path) -> Result<Option<String>> {
+        Ok(
+            serde_json::from_str::<JsonValue>(strip_bom(&read_to_string(path)?))?
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .map(String::from),
+        )
+    }
+
+    fn read_version(&self, path: &Path) -> Result<Option<Version>> {
+        Ok(
+            serde_json::from_str::<JsonValue>(strip_bom(&read_to_string(path)?))?
+                .get("version")
+                .and_then(JsonValue::as_str)
+                .and_then(parse_version),
+        )
+    }
+
+    fn plan_version(
+        &self,
+        path: &Path,
+        version: &Version,
+    ) -> Result<Vec<(PathBuf, ManifestUpdate)>> {
+        Ok(vec![(
+            path.to_path_buf(),
+            set_json_version(&read_to_string(path)?, version)?,
+        )])
+    }
+
+    fn version_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        Ok(vec![path.to_path_buf()])
+    }
+}
+
+struct SetupCfg;
+
+impl ManifestUpdater for SetupCfg {
+    fn package_name(&self, path: &Path) -> Result<Option<String>> {
+        let s = read_to_string(path)?;
+        Ok(setup_cfg_metadata_range(&s, "name").map(|r| String::from(&s[r])))
+    }
+
+    fn read_version(&self, path: &Path) -> Result<Option<Version>> {
+        let s = read_to_string(path)?;
+        Ok(setup_cfg_metadata_range(&s, "version").and_then(|r| parse_version(&s[r])))
+    }
+
+    fn plan_version(
+        &self,
+        path: &Path,
+        version: &Version,
+    ) -> Result<Vec<(PathBuf, ManifestUpdate)>> {
+        Ok(vec![(
+            path.to_path_buf(),
+            set_setup_cfg_version(&read_to_string(path)?, version),
+        )])
+    }
+
+    fn version_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        Ok(vec![path.to_path_buf()])
+    }
+}
+
+// Replaces the value of the version key in the [metadata] section, leaving
+// comments and all other keys and sections untouched
+pub fn set_setup_cfg_version(original: &str, version: &Version) -> ManifestUpdate {
+    let Some(range) = setup_cfg_metadata_range(original, "version") else {
+        return ManifestUpdate::NotApplicable;
+    };
+
+    let version = version.to_string();
+    if original[range.clone()] == version {
+        return ManifestUpdate::UpToDate;
+    }
+
+    let mut updated = String::with_capacity(original.len());
+    updated.push_str(&original[..range.start]);
+    updated.push_str(&version);
+    updated.push_str(&original[range.end..]);
+    ManifestUpdate::from_texts(original, updated)
+}
+
+// Finds the literal value of a key in the [metadata] section: setuptools
+// directives such as "attr:" and "file:" are not literal values
+fn setup_cfg_metadata_range(s: &str, key: &str) -> Option<Range<usize>> {
+    let mut in_metadata = false;
+    let mut offset = 0;
+    for line in s.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let trimmed = content.trim();
+        if trimmed.starts_with('[') {
+            in_metadata = trimmed == "[metadata]";
+        } else if in_metadata
+            && !content.starts_with([' ', '\t'])
+            && !trimmed.starts_with(['#', ';'])
+        {
+            if let Some(i) = content.find(['=', ':']) {
+                let value = content[i + 1..].trim_start();
+                if content[..i].trim().eq_ignore_ascii_case(key)
+                    && !value.is_empty()
+                    && !value.starts_with("attr:")
+                    && !value.starts_with("file:")
+                {
+                    let start = offset + content.len() - value.len();
+                    return Some(start..start + value.trim_end().len());
+                }
+            }
+        }
+        offset += line.len();
+    }
+    None
+}
+
+// Replaces the top-level "version" string in place so that key order,
+// indentation and everything else in the file are left untouched
+pub fn set_json_version(original: &str, version: &Version) -> Result<ManifestUpdate> {
+    let doc = serde_json::from_str::<JsonValue>(strip_bom(original))?;
+    if !doc.get("version").is_some_and(JsonValue::is_string) {
+        return Ok(ManifestUpdate::NotApplicable);
+    }
+
+    let Some(range) = json_version_range(original) else {
+        bail!("Could not locate top-level \"version\" key")
+    };
+
+    let mut updated = String::from(original);
+    updated.replace_range(range, &version.to_string());
+    Ok(ManifestUpdate::from_texts(original, updated))
+}
+
+// Location of the contents of the string value of the top-level "version"
+// key in a JSON object
+fn json_version_range(s: &str) -> Option<Range<usize>> {
+    // Returns the end of the string starting with the quote at index start
+    fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+        let mut i = start + 1;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => return Some(i + 1),
+                _ => i += 1,
+            }
+        }
+        None
+    }
+
+    fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    }
+
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b'"' => {
+                let end = string_end(bytes, i)?;
+                let colon = skip_whitespace(bytes, end);
+                if depth == 1 && &s[i..end] == "\"version\"" && bytes.get(colon) == Some(&b':') {
+                    let value_start = skip_whitespace(bytes, colon + 1);
+                    if bytes.get(value_start) != Some(&b'"') {
+                        return None;
+                    }
+                    return Some(value_start + 1..string_end(bytes, value_start)? - 1);
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+pub fn set_toml_version(
+    original: &str,
+    table_name: &str,
+    version: &Version,
+) -> Result<ManifestUpdate> {
+    set_toml_versions(original, &[(table_name, true)], version)
+}
+
+// Sets the shared version of a workspace and the version of a package unless
+// the package inherits its version using "version.workspace = true"
+pub fn set_cargo_version(original: &str, version: &Version) -> Result<ManifestUpdate> {
+    set_toml_versions(
+        original,
+        &[("workspace.package", false), ("package", true)],
+        version,
+    )
+}
+
+// Sets the string "version" key in each dotted table that exists, adding the
+// key if it is missing only when the table is marked for insertion
+fn set_toml_versions(
+    original: &str,
+    tables: &[(&str, bool)],
+    version: &Version,
+) -> Result<ManifestUpdate> {
+    let version = version.to_string();
+    let mut doc = strip_bom(original).parse::<DocumentMut>()?;
+    let mut applicable = false;
+    let mut changed = false;
+    for (table_name, insert) in tables {
+        let Some(table) = table_name
+            .split('.')
+            .try_fold(doc.as_item_mut(), |item, key| {
+                item.as_table_like_mut()?.get_mut(key)
+            })
+            .and_then(Item::as_table_like_mut)
+        else {
+            continue;
+        };
+
+        match table.get_mut("version") {
+            Some(item) => {
+                if let Some(v) = item.as_value_mut().filter(|v| v.is_str()) {
+                    if v.as_str() != Some(version.as_str()) {
+                        replace_value(v, &version);
+                        changed = true;
+                    }
+                    applicable = true;
+                }
+            }
+            None if *insert => {
+                _ = table.insert("version", value(version.as_str()));
+                applicable = true;
+                changed = true;
+            }
+            None => {}
+        }
+    }
+
+    Ok(if !applicable {
+        ManifestUpdate::NotApplicable
+    } else if changed {
+        ManifestUpdate::Changed(doc.to_string())
+    } else {
+        ManifestUpdate::UpToDate
+    })
+}
+
+pub fn update_version_keys(
+    path: &Path,
+    patterns: &[String],
+    excludes: &[String],
+    version: &Version,
+) -> Result<bool> {
+    set_version_keys(&read_to_string(path)?, patterns, excludes, version)?.write(path)
+}
+
+// Sets every string value whose dotted key path matches one of the patterns
+// and none of the excludes: "*" matches any single key
+pub fn set_version_keys(
+    original: &str,
+    patterns: &[String],
+    excludes: &[String],
+    version: &Version,
+) -> Result<ManifestUpdate> {
+    fn visit(
+        table: &mut dyn TableLike,
+        key_path: &mut Vec<String>,
+        patterns: &[String],
+        excludes: &[String],
+        version: &str,
+    ) {
+        for (key, item) in table.iter_mut() {
+            key_path.push(String::from(key.get()));
+            if let Some(t) = item.as_table_like_mut() {
+                visit(t, key_path, patterns, excludes, version);
+            } else if let Some(v) = item.as_value_mut() {
+                if v.as_str().is_some()
+                    && patterns.iter().any(|p| key_path_matches(p, key_path))
+                    && !excludes.iter().any(|p| key_path_matches(p, key_path))
+                {
+                    replace_value(v, version);
+                }
+            }
+            _ = key_path.pop();
+        }
+    }
+
+    let mut doc = strip_bom(original).parse::<DocumentMut>()?;
+    visit(
+        doc.as_table_mut(),
+        &mut Vec::new(),
+        patterns,
+        excludes,
+        &version.to_string(),
+    );
+    Ok(ManifestUpdate::from_texts(original, doc.to_string()))
+}
+
+// Keeps surrounding whitespace and trailing comments
+fn replace_value(v: &mut Value, s: &str) {
+    let decor = v.decor().clone();
+    *v = Value::from(s);
+    *v.decor_mut() = decor;
+}
+
+fn key_path_matches(pattern: &str, key_path: &[String]) -> bool {
+    let parts = pattern.split('.').collect::<Vec<_>>();
+    parts.len() == key_path.len()
+        && parts
+            .iter()
+            .zip(key_path)
+            .all(|(part, key)| *part == "*" || part == key)
+}
+
+pub fn update_version_manifest(
+    path: &Path,
+    table_name: &str,
+    package_names: &[String],
+    version: &Version,
+) -> Result<bool> {
+    let original = if path.is_file() {
+        read_to_string(path)?
+    } else {
+        String::new()
+    };
+    set_version_manifest(&original, table_name, package_names, version)?.write(path)
+}
+
+// Sets "<table>.<package> = version" for each package, creating the table
+// if necessary and leaving all other entries untouched
+pub fn set_version_manifest(
+    original: &str,
+    table_name: &str,
+    package_names: &[String],
+    version: &Version,
+) -> Result<ManifestUpdate> {
+    let version = version.to_string();
+    let mut doc = strip_bom(original).parse::<DocumentMut>()?;
+    let mut table = doc.as_table_mut();
+    for key in table_name.split('.') {
+        let Some(t) = table
+            .entry(key)
+            .or_insert_with(|| {
+                let mut t = Table::new();
+                t.set_implicit(true);
+                Item::Table(t)
+            })
+            .as_table_mut()
+        else {
+            bail!("{table_name} is not a table in version manifest")
+        };
+        table = t;
+    }
+
+    let mut changed = false;
+    for package_name in package_names {
+        match table.get_mut(package_name).and_then(Item::as_value_mut) {
+            Some(v) if v.as_str() == Some(version.as_str()) => {}
+            Some(v) => {
+                replace_value(v, &version);
+                changed = true;
+            }
+            None => {
+                _ = table.insert(package_name, value(version.as_str()));
+                changed = true;
+            }
+        }
+    }
+
+    if !changed {
+        return Ok(ManifestUpdate::UpToDate);
+    }
+
+    Ok(ManifestUpdate::from_texts(original, doc.to_string()))
+}
+
+pub fn set_version_file(original: &str, version: &Version) -> ManifestUpdate {
+    if original.trim() == version.to_string() {
+        return ManifestUpdate::UpToDate;
+    }
+
+    ManifestUpdate::from_texts(original, format!("{version}\n"))
+}
+
+fn parse_version(s: &str) -> Option<Version> {
+    s.trim().parse::<Version>().ok()
+}
+
+// Replaces the value of the first line for which value_range yields the
+// location of a version, leaving all other lines untouched
+fn set_line_version<F>(original: &str, version: &Version, value_range: F) -> ManifestUpdate
+where
+    F: Fn(&str) -> Option<Range<usize>>,
+{
+    let version = version.to_string();
+    let mut lines = original.split_inclusive('\n');
+    let mut updated = String::with_capacity(original.len());
+    for line in lines.by_ref() {
+        if let Some(range) = value_range(line) {
+            if line[range.clone()] == version {
+                return ManifestUpdate::UpToDate;
+            }
+
+            updated.push_str(&line[..range.start]);
+            updated.push_str(&version);
+            updated.push_str(&line[range.end..]);
+            updated.extend(lines);
+            return ManifestUpdate::from_texts(original, updated);
+        }
+
+        updated.push_str(line);
+    }
+
+    ManifestUpdate::NotApplicable
+}
+
+// Matches "version=1.2.3", "version: 1.2.3" or "version 1.2.3"
+fn properties_version_range(line: &str) -> Option<Range<usize>> {
+    let content = line.trim_end_matches(['\r', '\n']);
+    let rest = content.trim_start().strip_prefix("version")?;
+    let after_key = rest.trim_start_matches([' ', '\t']);
+    let value = match after_key.strip_prefix(['=', ':']) {
+        Some(s) => s.trim_start_matches([' ', '\t']),
+        None if after_key.len() < rest.len() => after_key,
+        None => return None,
+    };
+    let start = content.len() - value.len();
+    Some(start..start + value.trim_end().len())
+}
+
+// Matches version = "1.2.3" or version '1.2.3'
+fn gradle_build_version_range(line: &str) -> Option<Range<usize>> {
+    let content = line.trim_end_matches(['\r', '\n']);
+    let rest = content.trim_start().strip_prefix("version")?;
+    let after_key = rest.trim_start();
+    if after_key.len() == rest.len() && !after_key.starts_with('=') {
+        return None;
+    }
+
+    let after_sep = after_key
+        .strip_prefix('=')
+        .unwrap_or(after_key)
+        .trim_start();
+    let quote = after_sep
+        .chars()
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')?;
+    let value = &after_sep[1..];
+    let start = content.len() - value.len();
+    Some(start..start + value.find(quote)?)
+}
+
+fn pyproject_version(doc: &DocumentMut) -> Result<Option<PyprojectVersion>> {
+    let Some(project) = doc.get("project") else {
+        return Ok(None);
+    };
+
+    let is_dynamic = project
+        .get("dynamic")
+        .and_then(Item::as_array)
+        .is_some_and(|a| a.iter().any(|v| v.as_str() == Some("version")));
+    if !is_dynamic {
+        return Ok(Some(PyprojectVersion::Static));
+    }
+
+    let Some(file) = doc
+        .get("tool")
+        .and_then(|i| i.get("setuptools"))
+        .and_then(|i| i.get("dynamic"))
+        .and_then(|i| i.get("version"))
+        .and_then(|i| i.get("file"))
+    else {
+        bail!("Dynamic version in pyproject.toml is not backed by a file")
+    };
+
+    let paths = if let Some(s) = file.as_str() {
+        vec![PathBuf::from(s)]
+    } else if let Some(a) = file.as_array() {
+        a.iter()
+            .map(|v| {
+                v.as_str()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| anyhow!("Invalid dynamic version file {v}"))
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        bail!("Invalid dynamic version file {file}")
+    };
+
+    Ok(Some(PyprojectVersion::Files(paths)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        gradle_build_version_range, key_path_matches, properties_version_range, pyproject_version,
+        set_cargo_version, set_json_version, set_line_version, set_setup_cfg_version,
+        set_toml_version, set_version_file, set_version_keys, set_version_manifest, ManifestKind,
+        ManifestUpdate, ManifestUpdater, PyprojectVersion,
+    };
+    use crate::test_dir::with_test_dir;
+    use anyhow::Result;
+    use devtool_version::Version;
+    use rstest::rstest;
+    use std::fs::{read_to_string, write};
+    use std::path::{Path, PathBuf};
+    use toml_edit::DocumentMut;
+
+    #[rstest]
+    #[case(Some(ManifestKind::Cargo), "Cargo.toml")]
+    #[case(Some(ManifestKind::Cargo), "/project/crate/Cargo.toml")]
+    #[case(Some(ManifestKind::Pyproject), "pyproject.toml")]
+    #[case(Some(ManifestKind::Pyproject), "/project/python/pyproject.toml")]
+    #[case(Some(ManifestKind::Gradle), "gradle.properties")]
+    #[case(Some(ManifestKind::Gradle), "/project/app/build.gradle")]
+    #[case(Some(ManifestKind::Gradle), "build.gradle.kts")]
+    #[case(Some(ManifestKind::PackageJson), "/project/web/package.json")]
+    #[case(Some(ManifestKind::SetupCfg), "/project/python/setup.cfg")]
+    #[case(None, "setup.py")]
+    #[case(None, "cargo.toml")]
+    #[case(None, "Cargo.lock")]
+    #[case(None, "/project")]
+    fn detect(#[case] expected_result: Option<ManifestKind>, #[case] path: &str) {
+        assert_eq!(expected_result, ManifestKind::detect(Path::new(path)));
+    }
+
+    #[rstest]
+    #[case(
+        ManifestKind::Cargo,
+        "Cargo.toml",
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        "[package]\nname = \"foo\"\nversion = \"0.2.0\"\n"
+    )]
+    #[case(
+        ManifestKind::Pyproject,
+        "pyproject.toml",
+        "[project]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        "[project]\nname = \"foo\"\nversion = \"0.2.0\"\n"
+    )]
+    #[case(
+        ManifestKind::Gradle,
+        "gradle.properties",
+        "# Project\ngroup=com.example\nversion=0.1.0\norg.gradle.jvmargs=-Xmx2g\n",
+        "# Project\ngroup=com.example\nversion=0.2.0\norg.gradle.jvmargs=-Xmx2g\n"
+    )]
+    #[case(
+        ManifestKind::Gradle,
+        "build.gradle.kts",
+        "plugins {\n    kotlin(\"jvm\")\n}\n\nversion = \"0.1.0\"\n",
+        "plugins {\n    kotlin(\"jvm\")\n}\n\nversion = \"0.2.0\"\n"
+    )]
+    #[case(
+        ManifestKind::PackageJson,
+        "package.json",
+        "{\n  \"name\": \"foo\",\n  \"version\": \"0.1.0\",\n  \"private\": true\n}\n",
+        "{\n  \"name\": \"foo\",\n  \"version\": \"0.2.0\",\n  \"private\": true\n}\n"
+    )]
+    #[case(
+        ManifestKind::SetupCfg,
+        "setup.cfg",
+        "[metadata]\nname = foo\n# Bumped by devtool\nversion = 0.1.0\n\n[options]\npackages = find:\n",
+        "[metadata]\nname = foo\n# Bumped by devtool\nversion = 0.2.0\n\n[options]\npackages = find:\n"
+    )]
+    fn update_version(
+        #[case] kind: ManifestKind,
+        #[case] file_name: &str,
+        #[case] original: &str,
+        #[case] expected_result: &str,
+    ) -> Result<()> {
+        with_test_dir(&format!("manifests-{file_name}"), |dir| {
+            let path = dir.join(file_name);
+            write(&path, original)?;
+            let version = "0.2.0".parse::<Version>()?;
+
+            assert_eq!(
+                Some(String::from("0.1.0")),
+                kind.read_version(&path)?.map(|v| v.to_string())
+            );
+            assert!(kind.update_version(&path, &version)?);
+            assert_eq!(expected_result, read_to_string(&path)?);
+            assert_eq!(Some(version.clone()), kind.read_version(&path)?);
+            assert!(!kind.update_version(&path, &version)?);
+            assert_eq!(vec![path.clone()], kind.version_paths(&path)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn update_version_pyproject_file() -> Result<()> {
+        with_test_dir("manifests-pyproject-file", |dir| {
+            let path = dir.join("pyproject.toml");
+            let original = "[project]\nname = \"foo\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic]\nversion = { file = \"VERSION\" }\n";
+            write(&path, original)?;
+            write(dir.join("VERSION"), "0.1.0\n")?;
+            let version = "0.2.0".parse::<Version>()?;
+
+            assert!(ManifestKind::Pyproject.update_version(&path, &version)?);
+            assert_eq!(original, read_to_string(&path)?);
+            assert_eq!(Some(version), ManifestKind::Pyproject.read_version(&path)?);
+            assert_eq!("0.2.0\n", read_to_string(dir.join("VERSION"))?);
+            assert_eq!(
+                vec![dir.join("VERSION")],
+                ManifestKind::Pyproject.version_paths(&path)?
+            );
+            Ok(())
+        })
+    }
+
+    #[rstest]
+    #[case(
+        ManifestUpdate::Changed(String::from("[package]\nname = \"foo\"\nversion = \"0.2.0\"\n")),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        "package"
+    )]
+    #[case(
+        ManifestUpdate::UpToDate,
+        "[package]\nname = \"foo\"\nversion = \"0.2.0\"\n",
+        "package"
+    )]
+    #[case(
+        ManifestUpdate::UpToDate,
+        "\u{feff}[package]\nname = \"foo\"\nversion=\"0.2.0\"\n",
+        "package"
+    )]
+    #[case(
+        ManifestUpdate::UpToDate,
+        "[project]\nname = \"foo\"\nversion = \"0.2.0\"\n",
+        "project"
+    )]
+    #[case(
+        ManifestUpdate::NotApplicable,
+        "[workspace]\nmembers = [\"foo\"]\n",
+        "package"
+    )]
+    fn set_toml_version_basics(
+        #[case] expected_result: ManifestUpdate,
+        #[case] original: &str,
+        #[case] table_name: &str,
+    ) -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert_eq!(
+            expected_result,
+            set_toml_version(original, table_name, &version)?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        ManifestUpdate::Changed(String::from("[workspace]\nmembers = [\"foo\"]\n\n[workspace.package]\nversion = \"0.2.0\" # shared\nedition = \"2021\"\n")),
+        "[workspace]\nmembers = [\"foo\"]\n\n[workspace.package]\nversion = \"0.1.0\" # shared\nedition = \"2021\"\n"
+    )]
+    #[case(
+        ManifestUpdate::UpToDate,
+        "[workspace]\nmembers = [\"foo\"]\n\n[workspace.package]\nversion = \"0.2.0\"\n"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from("[workspace.package]\nversion = \"0.2.0\"\n\n[package]\nname = \"root\"\nversion.workspace = true\n")),
+        "[workspace.package]\nversion = \"0.1.0\"\n\n[package]\nname = \"root\"\nversion.workspace = true\n"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from("[workspace.package]\nedition = \"2021\"\n\n[package]\nname = \"root\"\nversion = \"0.2.0\"\n")),
+        "[workspace.package]\nedition = \"2021\"\n\n[package]\nname = \"root\"\nversion = \"0.1.0\"\n"
+    )]
+    #[case(
+        ManifestUpdate::NotApplicable,
+        "[package]\nname = \"foo\"\nversion.workspace = true\n"
+    )]
+    #[case(
+        ManifestUpdate::NotApplicable,
+        "[package]\nname = \"foo\"\nversion = { workspace = true }\n"
+    )]
+    #[case(ManifestUpdate::NotApplicable, "[workspace]\nmembers = [\"foo\"]\n")]
+    #[case(
+        ManifestUpdate::Changed(String::from("[package]\nname = \"foo\"\nversion = \"0.2.0\"\n")),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n"
+    )]
+    fn set_cargo_version_basics(
+        #[case] expected_result: ManifestUpdate,
+        #[case] original: &str,
+    ) -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert_eq!(expected_result, set_cargo_version(original, &version)?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        ManifestUpdate::Changed(String::from("# CI versions\n[versions]\nalpha = \"1.0.0\"\nfoo = \"0.2.0\" # main package\nzeta = \"3.1.4\"\n")),
+        "# CI versions\n[versions]\nalpha = \"1.0.0\"\nfoo = \"0.1.0\" # main package\nzeta = \"3.1.4\"\n",
+        "versions"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from(
+            "[packages.versions]\nalpha = \"1.0.0\"\nfoo = \"0.2.0\"\n"
+        )),
+        "[packages.versions]\nalpha = \"1.0.0\"\n",
+        "packages.versions"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from("[other]\nx = 1\n\n[versions]\nfoo = \"0.2.0\"\n")),
+        "[other]\nx = 1\n",
+        "versions"
+    )]
+    #[case(
+        ManifestUpdate::UpToDate,
+        "[versions]\nalpha = \"1.0.0\"\nfoo = \"0.2.0\"\n",
+        "versions"
+    )]
+    fn set_version_manifest_basics(
+        #[case] expected_result: ManifestUpdate,
+        #[case] original: &str,
+        #[case] table_name: &str,
+    ) -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert_eq!(
+            expected_result,
+            set_version_manifest(original, table_name, &[String::from("foo")], &version)?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(true, "package.version", &["package", "version"])]
+    #[case(true, "dependencies.*.version", &["dependencies", "foo", "version"])]
+    #[case(true, "*.*", &["package", "version"])]
+    #[case(false, "package.version", &["project", "version"])]
+    #[case(false, "package", &["package", "version"])]
+    #[case(false, "dependencies.*.version", &["dependencies", "version"])]
+    fn key_path_matches_basics(
+        #[case] expected_result: bool,
+        #[case] pattern: &str,
+        #[case] key_path: &[&str],
+    ) {
+        let key_path = key_path
+            .iter()
+            .map(|k| String::from(*k))
+            .collect::<Vec<_>>();
+        assert_eq!(expected_result, key_path_matches(pattern, &key_path));
+    }
+
+    #[test]
+    fn set_version_keys_excludes() -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        let result = set_version_keys(
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\" # bumped\n\n[dependencies]\nbar = { version = \"0.1.0\", path = \"../bar\" }\nserde = { version = \"1.0.0\" }\n",
+            &[
+                String::from("package.version"),
+                String::from("dependencies.*.version"),
+            ],
+            &[String::from("dependencies.serde.version")],
+            &version,
+        )?;
+        assert_eq!(
+            ManifestUpdate::Changed(String::from(
+                "[package]\nname = \"foo\"\nversion = \"0.2.0\" # bumped\n\n[dependencies]\nbar = { version = \"0.2.0\", path = \"../bar\" }\nserde = { version = \"1.0.0\" }\n"
+            )),
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn set_version_manifest_not_table() -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert!(set_version_manifest(
+            "versions = \"x\"\n",
+            "versions",
+            &[String::from("foo")],
+            &version
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(ManifestUpdate::Changed(String::from("0.2.0\n")), "0.1.0\n")]
+    #[case(ManifestUpdate::Changed(String::from("0.2.0\n")), "")]
+    #[case(ManifestUpdate::UpToDate, "0.2.0\n")]
+    #[case(ManifestUpdate::UpToDate, "0.2.0")]
+    fn set_version_file_basics(
+        #[case] expected_result: ManifestUpdate,
+        #[case] original: &str,
+    ) -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert_eq!(expected_result, set_version_file(original, &version));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        ManifestUpdate::Changed(String::from(
+            "group=com.example\nversion=0.2.0\nkotlin.code.style=official\n"
+        )),
+        "group=com.example\nversion=0.1.0\nkotlin.code.style=official\n"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from(
+            "# version=0.0.1\r\nversionCode=7\r\nversion = 0.2.0\r\nname=foo\r\n"
+        )),
+        "# version=0.0.1\r\nversionCode=7\r\nversion = 0.1.0\r\nname=foo\r\n"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from("version: 0.2.0\nversion=0.0.1\n")),
+        "version: 0.1.0\nversion=0.0.1\n"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from("version 0.2.0")),
+        "version 0.1.0"
+    )]
+    #[case(ManifestUpdate::UpToDate, "group=com.example\nversion=0.2.0\n")]
+    #[case(ManifestUpdate::NotApplicable, "group=com.example\nversionCode=7\n")]
+    fn set_line_version_properties(
+        #[case] expected_result: ManifestUpdate,
+        #[case] original: &str,
+    ) -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert_eq!(
+            expected_result,
+            set_line_version(original, &version, properties_version_range)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        ManifestUpdate::Changed(String::from(
+            "{\n    \"version\": \"0.2.0\",\n    \"name\": \"foo\"\n}\n"
+        )),
+        "{\n    \"version\": \"0.1.0\",\n    \"name\": \"foo\"\n}\n"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from(
+            "{\"dependencies\": {\"version\": \"1.0.0\"}, \"version\" : \"0.2.0\"}"
+        )),
+        "{\"dependencies\": {\"version\": \"1.0.0\"}, \"version\" : \"0.1.0\"}"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from(
+            "{\"description\": \"a \\\"version\\\": \\\"x\\\"\", \"version\": \"0.2.0\"}"
+        )),
+        "{\"description\": \"a \\\"version\\\": \\\"x\\\"\", \"version\": \"0.1.0\"}"
+    )]
+    #[case(ManifestUpdate::UpToDate, "{\"version\": \"0.2.0\"}")]
+    #[case(ManifestUpdate::NotApplicable, "{\"name\": \"foo\"}")]
+    #[case(ManifestUpdate::NotApplicable, "{\"version\": 2}")]
+    fn set_json_version_basics(
+        #[case] expected_result: ManifestUpdate,
+        #[case] original: &str,
+    ) -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert_eq!(expected_result, set_json_version(original, &version)?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        ManifestUpdate::Changed(String::from("[metadata]\r\nversion: 0.2.0 \r\n")),
+        "[metadata]\r\nversion: 0.1.0 \r\n"
+    )]
+    #[case(
+        ManifestUpdate::Changed(String::from(
+            "[options]\nversion = 9.9.9\n\n[metadata]\n; version = 0.0.1\nVersion=0.2.0\n"
+        )),
+        "[options]\nversion = 9.9.9\n\n[metadata]\n; version = 0.0.1\nVersion=0.1.0\n"
+    )]
+    #[case(ManifestUpdate::UpToDate, "[metadata]\nversion = 0.2.0\n")]
+    #[case(
+        ManifestUpdate::NotApplicable,
+        "[metadata]\nversion = attr: foo.__version__\n"
+    )]
+    #[case(ManifestUpdate::NotApplicable, "[metadata]\nversion_info = 0.1.0\n")]
+    #[case(ManifestUpdate::NotApplicable, "[bdist_wheel]\nversion = 0.1.0\n")]
+    fn set_setup_cfg_version_basics(
+        #[case] expected_result: ManifestUpdate,
+        #[case] original: &str,
+    ) -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert_eq!(expected_result, set_setup_cfg_version(original, &version));
+        Ok(())
+    }
+
+    #[test]
+    fn set_json_version_invalid() -> Result<()> {
+        let version = "0.2.0".parse::<Version>()?;
+        assert!(set_json_version("{\"version\": ", &version).is_err());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some(11..16), "version = \"1.2.3\"\n")]
+    #[case(Some(9..14), "version '1.2.3'")]
+    #[case(Some(9..14), "version=\"1.2.3\" // comment")]
+    #[case(None, "versionCode = 7\n")]
+    #[case(None, "version = project.findProperty(\"v\")\n")]
+    #[case(None, "group = \"com.example\"\n")]
+    fn gradle_build_version_range_basics(
+        #[case] expected_result: Option<std::ops::Range<usize>>,
+        #[case] line: &str,
+    ) {
+        assert_eq!(expected_result, gradle_build_version_range(line));
+    }
+
+    #[rstest]
+    #[case(None, "[tool.other]\nname = \"foo\"\n")]
+    #[case(
+        Some(PyprojectVersion::Static),
+        "[project]\nname = \"foo\"\nversion = \"0.1.0\"\n"
+    )]
+    #[case(
+        Some(PyprojectVersion::Static),
+        "[project]\nname = \"foo\"\ndynamic = [\"readme\"]\nversion = \"0.1.0\"\n"
+    )]
+    #[case(
+        Some(PyprojectVersion::Files(vec![PathBuf::from("pkg/VERSION")])),
+        "[project]\nname = \"foo\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic]\nversion = { file = \"pkg/VERSION\" }\n"
+    )]
+    #[case(
+        Some(PyprojectVersion::Files(vec![PathBuf::from("a/VERSION"), PathBuf::from("b/VERSION")])),
+        "[project]\nname = \"foo\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic.version]\nfile = [\"a/VERSION\", \"b/VERSION\"]\n"
+    )]
+    fn pyproject_version_basics(
+        #[case] expected_result: Option<PyprojectVersion>,
+        #[case] input: &str,
+    ) -> Result<()> {
+        assert_eq!(
+            expected_result,
+            pyproject_version(&input.parse::<DocumentMut>()?)?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("[project]\nname = \"foo\"\ndynamic = [\"version\"]\n")]
+    #[case(
+        "[project]\nname = \"foo\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic]\nversion = { attr = \"foo.__version__\" }\n"
+    )]
+    #[case(
+        "[project]\nname = \"foo\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic]\nversion = { file = 1 }\n"
+    )]
+    fn pyproject_version_invalid(#[case] input: &str) -> Result<()> {
+        assert!(pyproject_version(&input.parse::<DocumentMut>()?).is_err());
+        Ok(())
+    }
+}
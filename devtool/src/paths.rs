@@ -0,0 +1,99 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+const MAX_PATH: usize = 260;
+
+#[cfg(windows)]
+pub fn normalize_path(path: &Path) -> PathBuf {
+    path.to_str()
+        .map_or_else(|| path.to_path_buf(), |s| PathBuf::from(normalize_str(s)))
+}
+
+#[cfg(not(windows))]
+pub fn normalize_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+#[cfg(windows)]
+fn normalize_str(s: &str) -> String {
+    let s = s.replace('/', "\\");
+    let plain = if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{rest}")
+    } else if let Some(rest) = s.strip_prefix(r"\\?\") {
+        String::from(rest)
+    } else {
+        s
+    };
+
+    if plain.len() < MAX_PATH {
+        return plain;
+    }
+
+    plain.strip_prefix(r"\\").map_or_else(
+        || format!(r"\\?\{plain}"),
+        |rest| format!(r"\\?\UNC\{rest}"),
+    )
+}
+
+#[cfg(test)]
+#[cfg(windows)]
+mod tests {
+    use super::normalize_path;
+    use rstest::rstest;
+    use std::path::{Path, PathBuf};
+
+    #[rstest]
+    #[case(r"C:\foo\bar\Cargo.toml", r"C:\foo/bar/Cargo.toml")]
+    #[case(r"C:\foo\bar", r"\\?\C:\foo/bar")]
+    #[case(r"\\server\share\foo", r"\\?\UNC\server\share/foo")]
+    #[case(r"\\server\share\foo", r"//server/share/foo")]
+    fn short_paths(#[case] expected_result: &str, #[case] input: &str) {
+        assert_eq!(
+            PathBuf::from(expected_result),
+            normalize_path(Path::new(input))
+        );
+    }
+
+    #[test]
+    fn long_path() {
+        let component = "a".repeat(100);
+        let input = format!(r"C:\{component}/{component}/{component}");
+        assert_eq!(
+            PathBuf::from(format!(r"\\?\C:\{component}\{component}\{component}")),
+            normalize_path(Path::new(&input))
+        );
+    }
+
+    #[test]
+    fn long_unc_path() {
+        let component = "a".repeat(100);
+        let input = format!(r"\\server\share\{component}/{component}/{component}");
+        assert_eq!(
+            PathBuf::from(format!(
+                r"\\?\UNC\server\share\{component}\{component}\{component}"
+            )),
+            normalize_path(Path::new(&input))
+        );
+    }
+}
@@ -20,38 +20,185 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 use crate::app::App;
-use anyhow::Result;
+use crate::constants::IGNORE_FILE_NAME;
+use crate::manifests::ManifestKind;
+use crate::paths::normalize_path;
+use crate::serialization::Config;
+use anyhow::{bail, Result};
+use glob::{glob, Pattern};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use path_absolutize::Absolutize;
 use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs::read_dir;
 use std::path::{Path, PathBuf};
+use std::result::Result as StdResult;
+
+#[derive(Debug, PartialEq)]
+pub struct Ecosystem {
+    pub name: &'static str,
+    pub file_name: &'static str,
+    pub count: usize,
+}
 
 #[derive(Debug)]
+#[allow(clippy::struct_field_names)]
 pub struct ProjectInfo {
     pub cargo_toml_paths: Vec<PathBuf>,
     pub pyproject_toml_paths: Vec<PathBuf>,
+    pub gradle_paths: Vec<PathBuf>,
+    pub package_json_paths: Vec<PathBuf>,
+    pub setup_cfg_paths: Vec<PathBuf>,
+    pub setup_py_paths: Vec<PathBuf>,
 }
 
 impl ProjectInfo {
-    pub fn infer(app: &App) -> Result<Self> {
-        let cargo_toml_paths = Self::walk(
-            &app.git.dir,
-            |p| p.is_file() && p.file_name().map_or(false, |x| x == "Cargo.toml"),
-            &[OsStr::new(".git"), OsStr::new("target")],
+    pub fn read(app: &App, config: Option<&Config>, max_depth: Option<usize>) -> Result<Self> {
+        let mut project_info = config.map_or_else(
+            || Self::infer(app, max_depth),
+            |c| {
+                Ok(Self {
+                    cargo_toml_paths: expand_paths(&app.git.dir, &c.cargo_toml_paths)?,
+                    pyproject_toml_paths: expand_paths(&app.git.dir, &c.pyproject_toml_paths)?,
+                    gradle_paths: expand_paths(&app.git.dir, &c.gradle_paths)?,
+                    package_json_paths: expand_paths(&app.git.dir, &c.package_json_paths)?,
+                    setup_cfg_paths: expand_paths(&app.git.dir, &c.setup_cfg_paths)?,
+                    setup_py_paths: Vec::new(),
+                })
+            },
         )?;
-        let pyproject_toml_paths = Self::walk(
+        project_info.exclude_ignored(&app.git.dir)?;
+        Ok(project_info)
+    }
+
+    pub fn infer(app: &App, max_depth: Option<usize>) -> Result<Self> {
+        let paths = Self::walk(
             &app.git.dir,
-            |p| p.is_file() && p.file_name().map_or(false, |x| x == "pyproject.toml"),
-            &[OsStr::new(".git"), OsStr::new("target")],
+            |p| p.is_file() && (ManifestKind::detect(p).is_some() || is_setup_py(p)),
+            &[
+                OsStr::new(".git"),
+                OsStr::new(".gradle"),
+                OsStr::new("node_modules"),
+                OsStr::new("target"),
+            ],
+            max_depth,
         )?;
 
-        Ok(Self {
-            cargo_toml_paths,
-            pyproject_toml_paths,
-        })
+        let mut project_info = Self {
+            cargo_toml_paths: Vec::new(),
+            pyproject_toml_paths: Vec::new(),
+            gradle_paths: Vec::new(),
+            package_json_paths: Vec::new(),
+            setup_cfg_paths: Vec::new(),
+            setup_py_paths: Vec::new(),
+        };
+        for p in paths {
+            match ManifestKind::detect(&p) {
+                Some(ManifestKind::Cargo) => project_info.cargo_toml_paths.push(p),
+                Some(ManifestKind::Pyproject) => project_info.pyproject_toml_paths.push(p),
+                Some(ManifestKind::Gradle) => project_info.gradle_paths.push(p),
+                Some(ManifestKind::PackageJson) => project_info.package_json_paths.push(p),
+                Some(ManifestKind::SetupCfg) => project_info.setup_cfg_paths.push(p),
+                None if is_setup_py(&p) => project_info.setup_py_paths.push(p),
+                None => {}
+            }
+        }
+
+        Ok(project_info)
+    }
+
+    pub fn manifests(&self) -> Vec<(PathBuf, ManifestKind)> {
+        self.cargo_toml_paths
+            .iter()
+            .map(|p| (p.clone(), ManifestKind::Cargo))
+            .chain(
+                self.pyproject_toml_paths
+                    .iter()
+                    .map(|p| (p.clone(), ManifestKind::Pyproject)),
+            )
+            .chain(
+                self.gradle_paths
+                    .iter()
+                    .map(|p| (p.clone(), ManifestKind::Gradle)),
+            )
+            .chain(
+                self.package_json_paths
+                    .iter()
+                    .map(|p| (p.clone(), ManifestKind::PackageJson)),
+            )
+            .chain(
+                self.setup_cfg_paths
+                    .iter()
+                    .map(|p| (p.clone(), ManifestKind::SetupCfg)),
+            )
+            .collect()
+    }
+
+    pub fn ecosystems(&self) -> Vec<Ecosystem> {
+        vec![
+            Ecosystem {
+                name: "Cargo",
+                file_name: "Cargo.toml",
+                count: self.cargo_toml_paths.len(),
+            },
+            Ecosystem {
+                name: "Python (PEP 621)",
+                file_name: "pyproject.toml",
+                count: self.pyproject_toml_paths.len(),
+            },
+            Ecosystem {
+                name: "Gradle",
+                file_name: "gradle.properties",
+                count: self.gradle_paths.len(),
+            },
+            Ecosystem {
+                name: "npm",
+                file_name: "package.json",
+                count: self.package_json_paths.len(),
+            },
+            Ecosystem {
+                name: "Python (setuptools)",
+                file_name: "setup.cfg",
+                count: self.setup_cfg_paths.len(),
+            },
+        ]
+    }
+
+    fn exclude_ignored(&mut self, dir: &Path) -> Result<()> {
+        let ignore_path = dir.join(IGNORE_FILE_NAME);
+        if !ignore_path.is_file() {
+            return Ok(());
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        if let Some(e) = builder.add(&ignore_path) {
+            return Err(e.into());
+        }
+
+        self.exclude(dir, &builder.build()?);
+        Ok(())
+    }
+
+    fn exclude(&mut self, dir: &Path, gitignore: &Gitignore) {
+        let is_included = |p: &PathBuf| {
+            !p.starts_with(dir) || !gitignore.matched_path_or_any_parents(p, false).is_ignore()
+        };
+        self.cargo_toml_paths.retain(is_included);
+        self.pyproject_toml_paths.retain(is_included);
+        self.gradle_paths.retain(is_included);
+        self.package_json_paths.retain(is_included);
+        self.setup_cfg_paths.retain(is_included);
+        self.setup_py_paths.retain(is_included);
     }
 
-    fn walk<P>(start_dir: &Path, predicate: P, ignore_dirs: &[&OsStr]) -> Result<Vec<PathBuf>>
+    // Files directly inside start_dir are at depth 0 and directories deeper
+    // than max_depth are not visited
+    fn walk<P>(
+        start_dir: &Path,
+        predicate: P,
+        ignore_dirs: &[&OsStr],
+        max_depth: Option<usize>,
+    ) -> Result<Vec<PathBuf>>
     where
         P: Fn(&Path) -> bool,
     {
@@ -60,6 +207,8 @@ impl ProjectInfo {
             start_dir: &Path,
             predicate: &P,
             ignore_dirs_set: &HashSet<&OsStr>,
+            depth: usize,
+            max_depth: Option<usize>,
         ) -> Result<()>
         where
             P: Fn(&Path) -> bool,
@@ -69,11 +218,19 @@ impl ProjectInfo {
                 let path = entry.path();
 
                 if path.is_dir()
+                    && !matches!(max_depth, Some(m) if depth >= m)
                     && path
                         .file_name()
-                        .map_or(true, |x| !ignore_dirs_set.contains(x))
+                        .is_none_or(|x| !ignore_dirs_set.contains(x))
                 {
-                    helper(paths, &path, predicate, ignore_dirs_set)?;
+                    helper(
+                        paths,
+                        &path,
+                        predicate,
+                        ignore_dirs_set,
+                        depth + 1,
+                        max_depth,
+                    )?;
                 }
 
                 if predicate(&path) {
@@ -86,9 +243,278 @@ impl ProjectInfo {
 
         let mut paths = Vec::new();
         let ignore_dirs_set = ignore_dirs.iter().copied().collect::<HashSet<_>>();
-        helper(&mut paths, start_dir, &predicate, &ignore_dirs_set)?;
+        helper(
+            &mut paths,
+            start_dir,
+            &predicate,
+            &ignore_dirs_set,
+            0,
+            max_depth,
+        )?;
         paths.sort();
 
         Ok(paths)
     }
 }
+
+// Entries containing glob metacharacters are expanded relative to dir and
+// must match at least one file while literal paths are kept as they are
+fn expand_paths(dir: &Path, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut result = Vec::new();
+    for path in paths {
+        let Some(pattern) = path.to_str().filter(|s| s.contains(['*', '?', '['])) else {
+            let path = normalize_path(&path.absolutize_from(dir)?);
+            if !result.contains(&path) {
+                result.push(path);
+            }
+            continue;
+        };
+
+        let pattern = if path.is_absolute() {
+            String::from(pattern)
+        } else {
+            let Some(dir) = dir.to_str() else {
+                bail!("Directory {} is not valid UTF-8", dir.display())
+            };
+            format!("{}/{pattern}", Pattern::escape(dir))
+        };
+
+        let matches = glob(&pattern)?.collect::<StdResult<Vec<_>, _>>()?;
+        if matches.is_empty() {
+            bail!(
+                "Pattern \"{}\" in configuration file did not match any files",
+                path.display()
+            )
+        }
+
+        for path in matches {
+            let path = normalize_path(&path);
+            if !result.contains(&path) {
+                result.push(path);
+            }
+        }
+    }
+    Ok(result)
+}
+
+// Versions in setup.py are Python source and are not updated automatically
+fn is_setup_py(path: &Path) -> bool {
+    path.file_name() == Some(OsStr::new("setup.py"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_paths, Ecosystem, ProjectInfo};
+    use crate::manifests::ManifestKind;
+    use crate::test_dir::TestDir;
+    use anyhow::Result;
+    use ignore::gitignore::GitignoreBuilder;
+    use rstest::rstest;
+    use std::ffi::OsStr;
+    use std::fs::{create_dir_all, write};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn manifests() {
+        let project_info = ProjectInfo {
+            cargo_toml_paths: vec![PathBuf::from("/project/Cargo.toml")],
+            pyproject_toml_paths: vec![PathBuf::from("/project/python/pyproject.toml")],
+            gradle_paths: vec![PathBuf::from("/project/jvm/gradle.properties")],
+            package_json_paths: vec![PathBuf::from("/project/web/package.json")],
+            setup_cfg_paths: vec![PathBuf::from("/project/legacy/setup.cfg")],
+            setup_py_paths: vec![PathBuf::from("/project/legacy/setup.py")],
+        };
+        assert_eq!(
+            vec![
+                (PathBuf::from("/project/Cargo.toml"), ManifestKind::Cargo),
+                (
+                    PathBuf::from("/project/python/pyproject.toml"),
+                    ManifestKind::Pyproject
+                ),
+                (
+                    PathBuf::from("/project/jvm/gradle.properties"),
+                    ManifestKind::Gradle
+                ),
+                (
+                    PathBuf::from("/project/web/package.json"),
+                    ManifestKind::PackageJson
+                ),
+                (
+                    PathBuf::from("/project/legacy/setup.cfg"),
+                    ManifestKind::SetupCfg
+                )
+            ],
+            project_info.manifests()
+        );
+    }
+
+    #[test]
+    fn ecosystems() {
+        let project_info = ProjectInfo {
+            cargo_toml_paths: vec![
+                PathBuf::from("/project/Cargo.toml"),
+                PathBuf::from("/project/crate/Cargo.toml"),
+            ],
+            pyproject_toml_paths: vec![PathBuf::from("/project/python/pyproject.toml")],
+            gradle_paths: Vec::new(),
+            package_json_paths: Vec::new(),
+            setup_cfg_paths: Vec::new(),
+            setup_py_paths: Vec::new(),
+        };
+        assert_eq!(
+            vec![
+                Ecosystem {
+                    name: "Cargo",
+                    file_name: "Cargo.toml",
+                    count: 2
+                },
+                Ecosystem {
+                    name: "Python (PEP 621)",
+                    file_name: "pyproject.toml",
+                    count: 1
+                },
+                Ecosystem {
+                    name: "Gradle",
+                    file_name: "gradle.properties",
+                    count: 0
+                },
+                Ecosystem {
+                    name: "npm",
+                    file_name: "package.json",
+                    count: 0
+                },
+                Ecosystem {
+                    name: "Python (setuptools)",
+                    file_name: "setup.cfg",
+                    count: 0
+                }
+            ],
+            project_info.ecosystems()
+        );
+    }
+
+    #[test]
+    fn exclude() -> Result<()> {
+        let dir = Path::new("/project");
+        let mut builder = GitignoreBuilder::new(dir);
+        builder.add_line(None, "/vendor/Cargo.toml")?;
+        builder.add_line(None, "/third_party/")?;
+        let gitignore = builder.build()?;
+
+        let mut project_info = ProjectInfo {
+            cargo_toml_paths: vec![
+                PathBuf::from("/project/Cargo.toml"),
+                PathBuf::from("/project/vendor/Cargo.toml"),
+                PathBuf::from("/project/vendor/other/Cargo.toml"),
+                PathBuf::from("/project/third_party/lib/Cargo.toml"),
+            ],
+            pyproject_toml_paths: vec![
+                PathBuf::from("/project/pyproject.toml"),
+                PathBuf::from("/project/third_party/pyproject.toml"),
+            ],
+            gradle_paths: vec![PathBuf::from("/project/third_party/gradle.properties")],
+            package_json_paths: vec![
+                PathBuf::from("/project/package.json"),
+                PathBuf::from("/project/third_party/package.json"),
+            ],
+            setup_cfg_paths: vec![PathBuf::from("/project/third_party/setup.cfg")],
+            setup_py_paths: vec![
+                PathBuf::from("/project/setup.py"),
+                PathBuf::from("/project/third_party/setup.py"),
+            ],
+        };
+        project_info.exclude(dir, &gitignore);
+
+        assert_eq!(
+            vec![
+                PathBuf::from("/project/Cargo.toml"),
+                PathBuf::from("/project/vendor/other/Cargo.toml")
+            ],
+            project_info.cargo_toml_paths
+        );
+        assert_eq!(
+            vec![PathBuf::from("/project/pyproject.toml")],
+            project_info.pyproject_toml_paths
+        );
+        assert!(project_info.gradle_paths.is_empty());
+        assert_eq!(
+            vec![PathBuf::from("/project/package.json")],
+            project_info.package_json_paths
+        );
+        assert!(project_info.setup_cfg_paths.is_empty());
+        assert_eq!(
+            vec![PathBuf::from("/project/setup.py")],
+            project_info.setup_py_paths
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(vec!["Cargo.toml"], Some(0))]
+    #[case(vec!["Cargo.toml", "a/Cargo.toml"], Some(1))]
+    #[case(vec!["Cargo.toml", "a/Cargo.toml", "a/b/Cargo.toml"], Some(2))]
+    #[case(vec!["Cargo.toml", "a/Cargo.toml", "a/b/Cargo.toml"], None)]
+    fn walk(#[case] expected: Vec<&str>, #[case] max_depth: Option<usize>) -> Result<()> {
+        let test_dir = TestDir::new(&format!(
+            "project-info-walk-{}",
+            max_depth.map_or_else(|| String::from("none"), |m| m.to_string())
+        ))?;
+        let dir = test_dir.path();
+        create_dir_all(dir.join("a").join("b"))?;
+        create_dir_all(dir.join("target"))?;
+        write(dir.join("Cargo.toml"), "")?;
+        write(dir.join("a").join("Cargo.toml"), "")?;
+        write(dir.join("a").join("b").join("Cargo.toml"), "")?;
+        write(dir.join("target").join("Cargo.toml"), "")?;
+
+        let result = ProjectInfo::walk(
+            dir,
+            |p| p.file_name() == Some(OsStr::new("Cargo.toml")),
+            &[OsStr::new("target")],
+            max_depth,
+        );
+
+        assert_eq!(
+            expected.iter().map(|p| dir.join(p)).collect::<Vec<_>>(),
+            result?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expand_paths_globs() -> Result<()> {
+        let test_dir = TestDir::new("project-info-expand")?;
+        let dir = test_dir.path();
+        create_dir_all(dir.join("crates").join("a"))?;
+        create_dir_all(dir.join("crates").join("b"))?;
+        write(dir.join("Cargo.toml"), "")?;
+        write(dir.join("crates").join("a").join("Cargo.toml"), "")?;
+        write(dir.join("crates").join("b").join("Cargo.toml"), "")?;
+
+        let result = expand_paths(
+            dir,
+            &[
+                PathBuf::from("Cargo.toml"),
+                PathBuf::from("crates/*/Cargo.toml"),
+                PathBuf::from("crates/a/Cargo.toml"),
+                PathBuf::from("missing/Cargo.toml"),
+            ],
+        );
+        let no_match_result = expand_paths(dir, &[PathBuf::from("libs/*/Cargo.toml")]);
+
+        assert_eq!(
+            vec![
+                dir.join("Cargo.toml"),
+                dir.join("crates").join("a").join("Cargo.toml"),
+                dir.join("crates").join("b").join("Cargo.toml"),
+                dir.join("missing").join("Cargo.toml")
+            ],
+            result?
+        );
+        assert!(no_match_result
+            .unwrap_err()
+            .to_string()
+            .contains("libs/*/Cargo.toml"));
+        Ok(())
+    }
+}
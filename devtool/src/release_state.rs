@@ -0,0 +1,325 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use crate::app::App;
+use crate::commit_message_template::CommitMessageTemplate;
+use crate::constants::RELEASE_STATE_FILE_NAME;
+use anyhow::{bail, Error, Result};
+use chrono::DateTime;
+use devtool_git::CommitOptions;
+use joatmon::safe_write_file;
+use serde::{Deserialize, Serialize};
+use std::fs::{read_to_string, remove_file};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ReleaseState {
+    #[serde(rename = "version")]
+    pub version: String,
+
+    #[serde(rename = "tag")]
+    pub tag: String,
+
+    #[serde(rename = "commit_message", default)]
+    pub commit_message: Option<String>,
+
+    #[serde(rename = "tag_message_file", default)]
+    pub tag_message_file: Option<PathBuf>,
+
+    #[serde(rename = "sign", default)]
+    pub sign: bool,
+
+    #[serde(rename = "sign_commit", default)]
+    pub sign_commit: bool,
+
+    #[serde(rename = "commit_date", default)]
+    pub commit_date: Option<String>,
+
+    #[serde(rename = "no_verify", default)]
+    pub no_verify: bool,
+
+    #[serde(rename = "push_all", default)]
+    pub push_all: bool,
+
+    #[serde(rename = "tags_only", default)]
+    pub tags_only: bool,
+
+    #[serde(rename = "edited_files", default)]
+    pub edited_files: Vec<PathBuf>,
+
+    #[serde(rename = "commit_created", default)]
+    pub commit_created: bool,
+
+    #[serde(rename = "tag_created", default)]
+    pub tag_created: bool,
+
+    #[serde(rename = "pushed", default)]
+    pub pushed: bool,
+}
+
+impl ReleaseState {
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(RELEASE_STATE_FILE_NAME)
+    }
+
+    pub fn read(dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path(dir);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&read_to_string(path)?)?))
+    }
+
+    pub fn remove(dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        if path.is_file() {
+            remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    // Runs the release steps, saving progress to the state file if a step
+    // fails and removing any stale state file on success
+    pub fn record<F>(mut self, dir: &Path, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        match f(&mut self) {
+            Ok(()) => Self::remove(dir),
+            // Nothing to resume or roll back
+            Err(e) if !self.has_progress() => {
+                Self::remove(dir)?;
+                Err(e)
+            }
+            Err(e) => {
+                let path = Self::path(dir);
+                safe_write_file(&path, serde_json::to_string_pretty(&self)?, true)?;
+                Err(e.context(format!(
+                    "Release did not complete: progress was saved to {}: run devtool resume to continue or devtool resume --rollback to undo",
+                    path.display()
+                )))
+            }
+        }
+    }
+
+    const fn has_progress(&self) -> bool {
+        !self.edited_files.is_empty() || self.commit_created || self.tag_created || self.pushed
+    }
+
+    // Best-effort restore of files edited before the version bump commit
+    // was created: returns the original error with the outcome attached
+    pub fn rollback_edits(&mut self, app: &App, e: Error) -> Error {
+        if self.commit_created || self.edited_files.is_empty() {
+            return e;
+        }
+
+        match app.git.restore(&self.edited_files) {
+            Ok(()) => {
+                self.edited_files.clear();
+                e.context("Version bump failed: edited files were restored")
+            }
+            Err(rollback_e) => e.context(format!(
+                "Version bump failed and edited files could not be restored: {rollback_e}"
+            )),
+        }
+    }
+
+    pub fn commit(&mut self, app: &App) -> Result<()> {
+        app.git.commit_with_options(
+            self.commit_message
+                .clone()
+                .unwrap_or_else(|| CommitMessageTemplate::default().format(&self.version)),
+            &CommitOptions {
+                sign: self.sign_commit,
+                no_verify: self.no_verify,
+                date: self
+                    .commit_date
+                    .as_deref()
+                    .map(DateTime::parse_from_rfc3339)
+                    .transpose()?,
+                ..Default::default()
+            },
+        )?;
+        self.commit_created = true;
+        println!("Bumped package version to {}", self.version);
+        Ok(())
+    }
+
+    pub fn create_tag(&mut self, app: &App) -> Result<()> {
+        if self.sign {
+            app.git
+                .create_signed_tag(&self.tag, self.tag_message_file.as_deref())?;
+        } else {
+            app.git
+                .create_annotated_tag(&self.tag, self.tag_message_file.as_deref())?;
+        }
+        self.tag_created = true;
+        println!("Created tag {}", self.tag);
+        Ok(())
+    }
+
+    pub fn push(&mut self, app: &App) -> Result<()> {
+        if self.tags_only {
+            app.git.push_tags()?;
+            self.pushed = true;
+            println!("Pushed tags");
+        } else {
+            app.git.push_all()?;
+            self.pushed = true;
+            println!("Pushed commits and tags");
+        }
+        Ok(())
+    }
+
+    pub fn resume(&mut self, app: &App) -> Result<()> {
+        if !self.commit_created && !self.edited_files.is_empty() {
+            for path in &self.edited_files {
+                app.git.add(path)?;
+            }
+            self.commit(app)?;
+        }
+
+        if !self.tag_created {
+            self.create_tag(app)?;
+        }
+
+        if self.push_all && !self.pushed {
+            self.push(app)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn rollback(&self, app: &App) -> Result<()> {
+        if self.pushed {
+            bail!(
+                "Release {} was already pushed and cannot be rolled back",
+                self.tag
+            )
+        }
+
+        if self.tag_created {
+            app.git.delete_tag(&self.tag)?;
+            println!("Deleted tag {}", self.tag);
+        }
+
+        if self.commit_created {
+            app.git.reset_keep("HEAD~1")?;
+            println!("Removed version bump commit");
+        }
+
+        if !self.edited_files.is_empty() {
+            app.git.restore(&self.edited_files)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReleaseState;
+    use crate::test_dir::with_test_dir;
+    use anyhow::{anyhow, Result};
+    use std::path::{Path, PathBuf};
+
+    fn state() -> ReleaseState {
+        ReleaseState {
+            version: String::from("1.2.4"),
+            tag: String::from("v1.2.4"),
+            push_all: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn record_failure() -> Result<()> {
+        with_test_dir("release-state-failure", |dir| {
+            let result = state().record(dir, |state| {
+                state.edited_files.push(dir.join("Cargo.toml"));
+                state.edited_files.push(dir.join("Cargo.lock"));
+                state.commit_created = true;
+                Err(anyhow!("tag already exists"))
+            });
+
+            let message = format!("{:#}", result.unwrap_err());
+            assert!(message.contains("devtool resume"));
+            assert!(message.contains("tag already exists"));
+            assert_eq!(
+                Some(ReleaseState {
+                    edited_files: vec![dir.join("Cargo.toml"), dir.join("Cargo.lock")],
+                    commit_created: true,
+                    ..state()
+                }),
+                ReleaseState::read(dir)?
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn record_success() -> Result<()> {
+        with_test_dir("release-state-success", |dir| {
+            _ = state().record(dir, |state| {
+                state.tag_created = true;
+                Err(anyhow!("push failed"))
+            });
+            assert!(ReleaseState::path(dir).is_file());
+
+            state().record(dir, |state| {
+                state.tag_created = true;
+                Ok(())
+            })?;
+            assert!(!ReleaseState::path(dir).exists());
+            assert_eq!(None, ReleaseState::read(dir)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn record_failure_without_progress() -> Result<()> {
+        with_test_dir("release-state-failure-without-progress", |dir| {
+            _ = state().record(dir, |state| {
+                state.tag_created = true;
+                Err(anyhow!("push failed"))
+            });
+            assert!(ReleaseState::path(dir).is_file());
+
+            let result = state().record(dir, |_| Err(anyhow!("Cargo.lock update failed")));
+
+            let message = format!("{:#}", result.unwrap_err());
+            assert!(!message.contains("devtool resume"));
+            assert!(message.contains("Cargo.lock update failed"));
+            assert!(!ReleaseState::path(dir).exists());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn path() {
+        assert_eq!(
+            PathBuf::from("/project/.devtool-release-state.json"),
+            ReleaseState::path(Path::new("/project"))
+        );
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use anyhow::{bail, Result};
+use colored::Colorize;
+use std::io::{stdout, Write};
+
+#[derive(Debug, Default)]
+pub struct Reporter {
+    pub strict: bool,
+}
+
+impl Reporter {
+    pub fn warn(&self, message: &str) -> Result<()> {
+        self.warn_to(&mut stdout(), message)
+    }
+
+    fn warn_to<W: Write>(&self, writer: &mut W, message: &str) -> Result<()> {
+        if self.strict {
+            bail!("{message} (warning treated as error because of --strict)")
+        }
+
+        writeln!(writer, "{}", format!("Warning: {message}").yellow())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reporter;
+    use crate::test_colour::strip_colour;
+    use anyhow::Result;
+
+    #[test]
+    fn warn_lenient() -> Result<()> {
+        let mut output = Vec::new();
+        Reporter { strict: false }.warn_to(&mut output, "tag has drifted")?;
+        assert_eq!(
+            "Warning: tag has drifted\n",
+            strip_colour(&String::from_utf8(output)?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn warn_strict() {
+        let mut output = Vec::new();
+        let result = Reporter { strict: true }.warn_to(&mut output, "tag has drifted");
+        assert!(result.unwrap_err().to_string().contains("tag has drifted"));
+        assert!(output.is_empty());
+    }
+}
@@ -20,14 +20,22 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 use crate::app::App;
-use crate::args::{Args, Command};
-use crate::commands::{bump_version, generate_config, generate_ignore, scratch, show_description};
-use crate::logging::init_logging;
-use anyhow::{anyhow, Result};
+use crate::args::{bump_kind, flag_pair, Args, Command};
+use crate::commands::{
+    bump_version, check_manifests, completions, generate_config, generate_ignore, list_ecosystems,
+    list_tags, prune_tags, resume, scratch, show_description, undo_bump, BumpVersionOptions,
+    ShowDescriptionOptions,
+};
+use crate::error::{DevtoolError, DevtoolResult};
+use crate::logging::{init_logging, LoggingOptions};
+use anyhow::anyhow;
 use clap::Parser;
 use joatmon::{find_sentinel_dir, find_sentinel_file};
+use log::LevelFilter;
 use std::env::current_dir;
+use std::io::{stdout, IsTerminal};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 fn infer_git_dir(cwd: &Path) -> Option<PathBuf> {
     let git_path = Path::new(".git");
@@ -53,29 +61,175 @@ fn infer_git_dir(cwd: &Path) -> Option<PathBuf> {
     }
 }
 
-pub fn run() -> Result<()> {
+fn infer_project_dir(cwd: &Path) -> Option<PathBuf> {
+    ["Cargo.toml", "pyproject.toml"]
+        .iter()
+        .filter_map(|file_name| find_sentinel_file(Path::new(file_name), cwd, None))
+        .map(|mut p| {
+            p.pop();
+            p
+        })
+        .max_by_key(|d| d.as_os_str().len())
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn run() -> DevtoolResult<()> {
     let cwd = current_dir()?;
     let args = Args::parse();
 
-    init_logging(args.detailed, args.log_level)?;
+    init_logging(LoggingOptions {
+        detailed: args.detailed,
+        level_filter: if args.quiet {
+            LevelFilter::Warn
+        } else {
+            args.log_level
+        },
+        log_format: args.log_format,
+        time_format: args.log_time_format,
+        log_file: args.log_file,
+    })?;
 
-    let git_dir = args
-        .git_dir
-        .or_else(|| infer_git_dir(&cwd))
-        .ok_or_else(|| anyhow!("Cannot infer Git project directory"))?;
+    if args.no_color || !stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
 
-    let app = App::new(git_dir);
+    // The project directory is only resolved for commands that operate on one
+    let requires_git = args.command.requires_git();
+    let app = || -> DevtoolResult<App> {
+        let git_dir = if args.no_git {
+            if requires_git {
+                return Err(DevtoolError::NoGitRepository(anyhow!(
+                    "This command requires a Git repository and cannot be used with --no-git"
+                )));
+            }
+
+            args.git_dir
+                .or_else(|| infer_project_dir(&cwd))
+                .ok_or_else(|| anyhow!("Cannot infer project directory"))?
+        } else {
+            args.git_dir
+                .or_else(|| infer_git_dir(&cwd))
+                .ok_or_else(|| {
+                    DevtoolError::NoGitRepository(anyhow!("Cannot infer Git project directory"))
+                })?
+        };
+
+        let mut app = App::new(git_dir);
+        app.git.print_commands = args.print_git_commands;
+        app.git.push_retries = args.push_retries;
+        app.git.timeout = Duration::from_secs(args.git_timeout);
+        app.start_dir = cwd;
+        app.config_search_stop = args.config_search_stop;
+        app.global_config = args.global_config;
+        app.reporter.strict = args.strict;
+        Ok(app)
+    };
 
     match args.command {
         Command::BumpVersion {
             version,
+            major,
+            minor,
+            patch,
+            zerover,
+            no_zerover,
             push_all,
-            _no_push_all,
-        } => bump_version(&app, &version, push_all)?,
-        Command::GenerateConfig => generate_config(&app)?,
-        Command::GenerateIgnore => generate_ignore(&app)?,
-        Command::Scratch => scratch(&app),
-        Command::ShowDescription => show_description(&app)?,
+            no_push_all,
+            tags_only,
+            push_dry_run,
+            on_no_commits,
+            on_drift,
+            on_no_manifests,
+            tag_template,
+            tag_match,
+            dirty_tree_policy,
+            lock_strategy,
+            tag_message_file,
+            tag_prefix,
+            no_tag_prefix,
+            commit_message_template,
+            only_if_changed,
+            sign,
+            sign_commit,
+            commit_date,
+            github_release,
+            exclude_patterns,
+            allowed_branches,
+            allow_dirty_config,
+            list_files,
+            dry_run,
+            changelog,
+            ignore_untracked,
+            max_depth,
+            fetch,
+            no_verify,
+        } => bump_version(
+            &app()?,
+            BumpVersionOptions {
+                version,
+                bump: bump_kind(major, minor, patch),
+                zerover: flag_pair(zerover, no_zerover),
+                push_all: flag_pair(push_all, no_push_all),
+                tags_only,
+                push_dry_run,
+                on_no_commits,
+                on_drift,
+                on_no_manifests,
+                tag_template,
+                tag_match,
+                dirty_tree_policy,
+                lock_strategy,
+                tag_message_file,
+                tag_prefix: flag_pair(tag_prefix, no_tag_prefix),
+                commit_message_template,
+                only_if_changed,
+                sign,
+                sign_commit,
+                commit_date,
+                github_release,
+                exclude_patterns,
+                allowed_branches,
+                allow_dirty_config,
+                list_files,
+                dry_run,
+                changelog,
+                ignore_untracked,
+                max_depth,
+                fetch,
+                no_verify,
+            },
+        )?,
+        Command::CheckManifests => check_manifests(&app()?)?,
+        Command::Completions { shell } => completions(shell),
+        Command::GenerateConfig { dry_run } => generate_config(&app()?, dry_run)?,
+        Command::GenerateIgnore {
+            write,
+            force,
+            append,
+            templates,
+        } => generate_ignore(&app()?, write, force, append, templates)?,
+        Command::ListEcosystems => list_ecosystems(&app()?)?,
+        Command::ListTags => list_tags(&app()?)?,
+        Command::PruneTags { remote, confirm } => prune_tags(&app()?, &remote, confirm)?,
+        Command::Resume { rollback } => resume(&app()?, rollback)?,
+        Command::Scratch => scratch(&app()?),
+        Command::UndoBump => undo_bump(&app()?)?,
+        Command::ShowDescription {
+            dirty_tree_policy,
+            contributors,
+            verbose,
+            json,
+        } => {
+            show_description(
+                &app()?,
+                ShowDescriptionOptions {
+                    dirty_tree_policy,
+                    contributors,
+                    verbose,
+                    json,
+                },
+            )?;
+        }
     }
     Ok(())
 }
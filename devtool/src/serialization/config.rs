@@ -19,14 +19,91 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
+use super::VersionManifest;
+use crate::dirty_tree_policy::DirtyTreePolicy;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 #[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(rename = "cargo_toml_paths", default)]
     pub cargo_toml_paths: Vec<PathBuf>,
 
     #[serde(rename = "pyproject_toml_paths", default)]
     pub pyproject_toml_paths: Vec<PathBuf>,
+
+    #[serde(rename = "gradle_paths", default)]
+    pub gradle_paths: Vec<PathBuf>,
+
+    #[serde(rename = "package_json_paths", default)]
+    pub package_json_paths: Vec<PathBuf>,
+
+    #[serde(rename = "setup_cfg_paths", default)]
+    pub setup_cfg_paths: Vec<PathBuf>,
+
+    #[serde(
+        rename = "tag_template",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tag_template: Option<String>,
+
+    #[serde(rename = "tag_match", default, skip_serializing_if = "Option::is_none")]
+    pub tag_match: Option<String>,
+
+    #[serde(
+        rename = "tag_prefix",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tag_prefix: Option<bool>,
+
+    #[serde(rename = "zerover", default, skip_serializing_if = "Option::is_none")]
+    pub zerover: Option<bool>,
+
+    #[serde(
+        rename = "commit_message_template",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub commit_message_template: Option<String>,
+
+    #[serde(
+        rename = "dirty_tree_policy",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub dirty_tree_policy: Option<DirtyTreePolicy>,
+
+    #[serde(rename = "push", default, skip_serializing_if = "Option::is_none")]
+    pub push: Option<bool>,
+
+    #[serde(
+        rename = "allowed_branches",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub allowed_branches: Vec<String>,
+
+    #[serde(
+        rename = "version_manifest",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub version_manifest: Option<VersionManifest>,
+
+    #[serde(
+        rename = "version_keys",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub version_keys: Vec<String>,
+
+    #[serde(
+        rename = "version_key_excludes",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub version_key_excludes: Vec<String>,
 }
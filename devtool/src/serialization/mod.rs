@@ -20,5 +20,7 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //
 mod config;
+mod version_manifest;
 
 pub use self::config::Config;
+pub use self::version_manifest::VersionManifest;
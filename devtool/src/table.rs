@@ -0,0 +1,85 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use colored::control::SHOULD_COLORIZE;
+use colored::Colorize;
+
+#[derive(Debug, Default)]
+pub struct Table {
+    rows: Vec<(String, String)>,
+}
+
+impl Table {
+    pub fn add_row<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.rows.push((key.into(), value.into()));
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.render(SHOULD_COLORIZE.should_colorize()));
+    }
+
+    fn render(&self, color: bool) -> String {
+        let width = self
+            .rows
+            .iter()
+            .map(|(k, _)| k.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        self.rows
+            .iter()
+            .map(|(k, v)| {
+                let key = format!("{k:<width$}");
+                if color {
+                    format!("{}  {v}\n", key.cyan())
+                } else {
+                    format!("{key}  {v}\n")
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+
+    #[test]
+    fn render_plain() {
+        let mut table = Table::default();
+        table.add_row("Description", "v1.2.3-2-gabcdef0");
+        table.add_row("Tag", "v1.2.3");
+        table.add_row("Version", "v1.2.3");
+        assert_eq!(
+            "Description  v1.2.3-2-gabcdef0\nTag          v1.2.3\nVersion      v1.2.3\n",
+            table.render(false)
+        );
+    }
+
+    #[test]
+    fn render_empty() {
+        assert_eq!("", Table::default().render(false));
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use anyhow::{bail, Result};
+use devtool_version::Version;
+
+#[derive(Clone, Copy, Debug)]
+enum Placeholder {
+    Prefix,
+    Version,
+    Major,
+    Minor,
+    Patch,
+}
+
+impl Placeholder {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "prefix" => Some(Self::Prefix),
+            "version" => Some(Self::Version),
+            "major" => Some(Self::Major),
+            "minor" => Some(Self::Minor),
+            "patch" => Some(Self::Patch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Segment {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug)]
+pub struct TagTemplate {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+impl TagTemplate {
+    pub fn new<S>(template: S) -> Result<Self>
+    where
+        S: Into<String>,
+    {
+        let template = template.into();
+        let mut segments = Vec::new();
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}').map(|i| start + i) else {
+                bail!("Unterminated placeholder in tag template \"{template}\"")
+            };
+
+            let name = &rest[start + 1..end];
+            let Some(placeholder) = Placeholder::from_name(name) else {
+                bail!("Unknown placeholder {{{name}}} in tag template \"{template}\"")
+            };
+
+            if start > 0 {
+                segments.push(Segment::Literal(String::from(&rest[..start])));
+            }
+            segments.push(Segment::Placeholder(placeholder));
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(String::from(rest)));
+        }
+
+        if !segments
+            .iter()
+            .any(|s| matches!(s, Segment::Placeholder(p) if !matches!(p, Placeholder::Prefix)))
+        {
+            bail!("Tag template \"{template}\" must contain a version placeholder")
+        }
+
+        Ok(Self { template, segments })
+    }
+
+    pub fn format(&self, version: &Version) -> String {
+        let mut version_without_prefix = version.dupe();
+        version_without_prefix.set_prefix(false);
+        let version_str = version_without_prefix.to_string();
+        let prefix = if version.to_string().starts_with('v') {
+            "v"
+        } else {
+            ""
+        };
+
+        let major = version.major().to_string();
+        let minor = version.minor().unwrap_or_default().to_string();
+        let patch = version.patch().unwrap_or_default().to_string();
+
+        let mut result = String::new();
+        for segment in &self.segments {
+            result.push_str(match segment {
+                Segment::Literal(s) => s,
+                Segment::Placeholder(Placeholder::Prefix) => prefix,
+                Segment::Placeholder(Placeholder::Version) => &version_str,
+                Segment::Placeholder(Placeholder::Major) => &major,
+                Segment::Placeholder(Placeholder::Minor) => &minor,
+                Segment::Placeholder(Placeholder::Patch) => &patch,
+            });
+        }
+        result
+    }
+
+    pub fn glob(&self) -> String {
+        let mut result = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(s) => result.push_str(s),
+                Segment::Placeholder(_) if result.ends_with('*') => {}
+                Segment::Placeholder(_) => result.push('*'),
+            }
+        }
+        result
+    }
+
+    pub fn parse_version(&self, tag: &str) -> Result<Version> {
+        let head = match self.segments.first() {
+            Some(Segment::Literal(s)) => s.as_str(),
+            _ => "",
+        };
+        let tail = match self.segments.last() {
+            Some(Segment::Literal(s)) => s.as_str(),
+            _ => "",
+        };
+        let Some(s) = tag.strip_prefix(head).and_then(|s| s.strip_suffix(tail)) else {
+            bail!(
+                "Tag \"{tag}\" does not match tag template \"{}\"",
+                self.template
+            )
+        };
+
+        Ok(s.parse::<Version>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TagTemplate;
+    use anyhow::Result;
+    use devtool_version::Version;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("release/1.2.3", "release/{version}", "v1.2.3")]
+    #[case("v1.2.3", "{prefix}{version}", "v1.2.3")]
+    #[case("1.2.3", "{prefix}{version}", "1.2.3")]
+    #[case("v1.2.0", "{prefix}{major}.{minor}.{patch}", "v1.2")]
+    #[case("release-1.2", "release-{major}.{minor}", "1.2.3")]
+    fn format(
+        #[case] expected_result: &str,
+        #[case] template: &str,
+        #[case] version: &str,
+    ) -> Result<()> {
+        let template = TagTemplate::new(template)?;
+        assert_eq!(
+            expected_result,
+            template.format(&version.parse::<Version>()?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some("1.2.3"), "release/{version}", "release/1.2.3")]
+    #[case(Some("v1.2.3"), "{prefix}{version}", "v1.2.3")]
+    #[case(
+        Some("v1.2.3"),
+        "{prefix}{major}.{minor}.{patch}-final",
+        "v1.2.3-final"
+    )]
+    #[case(None, "release/{version}", "v1.2.3")]
+    #[case(None, "{version}-final", "1.2.3")]
+    fn parse_version(
+        #[case] expected_result: Option<&str>,
+        #[case] template: &str,
+        #[case] tag: &str,
+    ) -> Result<()> {
+        let template = TagTemplate::new(template)?;
+        match expected_result {
+            Some(expected_version) => {
+                assert_eq!(expected_version, template.parse_version(tag)?.to_string());
+            }
+            None => assert!(template.parse_version(tag).is_err()),
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("release")]
+    #[case("{prefix}")]
+    #[case("{foo}")]
+    #[case("{version")]
+    fn new_invalid(#[case] template: &str) {
+        assert!(TagTemplate::new(template).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use anyhow::Result;
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::{Path, PathBuf};
+use std::process;
+
+// Scratch directory that is removed on drop so that failed assertions do not leak it
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    pub fn new(name: &str) -> Result<Self> {
+        let path = temp_dir().join(format!("devtool-{name}-{}", process::id()));
+        create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        _ = remove_dir_all(&self.0);
+    }
+}
+
+pub fn with_test_dir<F>(name: &str, f: F) -> Result<()>
+where
+    F: FnOnce(&Path) -> Result<()>,
+{
+    let dir = TestDir::new(name)?;
+    f(dir.path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestDir;
+    use anyhow::{bail, Result};
+    use std::fs::write;
+    use std::panic::catch_unwind;
+    use std::path::PathBuf;
+
+    #[test]
+    fn removed_on_drop() -> Result<()> {
+        let path = {
+            let dir = TestDir::new("test-dir-drop")?;
+            write(dir.path().join("file.txt"), "")?;
+            dir.path().to_path_buf()
+        };
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn removed_on_panic() -> Result<()> {
+        let Err(payload) = catch_unwind(|| -> PathBuf {
+            let dir = TestDir::new("test-dir-panic").expect("must succeed");
+            panic!("{}", dir.path().display())
+        }) else {
+            bail!("must panic")
+        };
+        let path = payload
+            .downcast_ref::<String>()
+            .map(PathBuf::from)
+            .expect("must be string");
+        assert!(!path.exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2023 Richard Cook
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the
+// "Software"), to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use std::fs::read_to_string;
+use std::path::Path;
+use toml_edit::DocumentMut;
+
+const BOM: char = '\u{feff}';
+
+// Leading byte order marks are dropped on read and are never written back
+pub fn strip_bom(s: &str) -> &str {
+    s.strip_prefix(BOM).unwrap_or(s)
+}
+
+pub fn read_toml_file_edit(path: &Path) -> Result<DocumentMut> {
+    parse_toml(&read_to_string(path)?)
+}
+
+pub fn read_yaml_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    parse_yaml(&read_to_string(path)?)
+}
+
+fn parse_toml(s: &str) -> Result<DocumentMut> {
+    Ok(strip_bom(s).parse::<DocumentMut>()?)
+}
+
+fn parse_yaml<T: DeserializeOwned>(s: &str) -> Result<T> {
+    Ok(serde_yaml::from_str(strip_bom(s))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_toml, parse_yaml, strip_bom, BOM};
+    use crate::serialization::Config;
+    use anyhow::Result;
+    use rstest::rstest;
+    use std::path::PathBuf;
+    use toml_edit::value;
+
+    #[rstest]
+    #[case("abc", "abc")]
+    #[case("abc", "\u{feff}abc")]
+    #[case("\u{feff}abc", "\u{feff}\u{feff}abc")]
+    #[case("", "\u{feff}")]
+    fn strip_bom_basics(#[case] expected_result: &str, #[case] input: &str) {
+        assert_eq!(expected_result, strip_bom(input));
+    }
+
+    #[test]
+    fn rewrite_toml_with_bom() -> Result<()> {
+        let mut doc = parse_toml("\u{feff}[package]\nname = \"foo\"\nversion = \"0.1.0\"\n")?;
+        _ = doc["package"]
+            .as_table_mut()
+            .expect("must be table")
+            .insert("version", value("0.2.0"));
+        let output = doc.to_string();
+        assert!(!output.contains(BOM));
+        assert_eq!("[package]\nname = \"foo\"\nversion = \"0.2.0\"\n", output);
+        Ok(())
+    }
+
+    #[test]
+    fn read_yaml_with_bom() -> Result<()> {
+        let config = parse_yaml::<Config>("\u{feff}cargo_toml_paths:\n- Cargo.toml\n")?;
+        assert_eq!(vec![PathBuf::from("Cargo.toml")], config.cargo_toml_paths);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("cargo_toml_path", "cargo_toml_path:\n- Cargo.toml\n")]
+    #[case("file", "version_manifest:\n  path: versions.toml\n  file: versions\n")]
+    fn read_yaml_unknown_field(#[case] expected_key: &str, #[case] input: &str) {
+        let message = parse_yaml::<Config>(input).unwrap_err().to_string();
+        assert!(message.contains(&format!("unknown field `{expected_key}`")));
+    }
+}